@@ -0,0 +1,207 @@
+// How answers reach the server. The real site only speaks HTTPS, which std
+// cannot do on its own, so that goes through curl; plain HTTP (the mock server)
+// is spoken directly over a TcpStream. Anything else, a proxy or a recording
+// for tests, only has to implement `HttpClient`.
+use crate::SubmitError;
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+const USER_AGENT: &str = "adventofcode2023 aoc-submit (std)";
+
+pub trait HttpClient {
+    /// POSTs `form` url-encoded to `url` with the session cookie and returns
+    /// the response body. Non-2xx statuses are errors.
+    fn post_form(
+        &self,
+        url: &str,
+        session: &str,
+        form: &[(&str, &str)],
+    ) -> Result<String, SubmitError>;
+}
+
+/// Picks curl for https and the built-in client for plain http.
+pub fn client_for(url: &str) -> Box<dyn HttpClient> {
+    if url.starts_with("http://") {
+        Box::new(TcpClient)
+    } else {
+        Box::new(CurlClient)
+    }
+}
+
+pub struct CurlClient;
+
+impl CurlClient {
+    // Everything but the session cookie, which any local user could read from
+    // the command line. That one goes in `config` on curl's stdin.
+    fn command(url: &str, form: &[(&str, &str)]) -> Command {
+        let mut command = Command::new("curl");
+        command.args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--user-agent",
+            USER_AGENT,
+            "--config",
+            "-",
+        ]);
+        for (key, value) in form {
+            command.args(["--data-urlencode", &format!("{}={}", key, value)]);
+        }
+        command.arg(url);
+        command
+    }
+
+    // curl config file syntax, quoted so the session is taken verbatim
+    fn config(session: &str) -> String {
+        format!(
+            "cookie = \"session={}\"\n",
+            session.replace('\\', "\\\\").replace('"', "\\\"")
+        )
+    }
+}
+
+impl HttpClient for CurlClient {
+    fn post_form(
+        &self,
+        url: &str,
+        session: &str,
+        form: &[(&str, &str)],
+    ) -> Result<String, SubmitError> {
+        let curl_error =
+            |e: std::io::Error| SubmitError::Http(format!("could not run curl: {}", e));
+        let mut child = CurlClient::command(url, form)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(curl_error)?;
+        // dropping stdin closes it, so curl stops reading config
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(CurlClient::config(session).as_bytes())
+                .map_err(curl_error)?;
+        }
+        let output = child.wait_with_output().map_err(curl_error)?;
+        if !output.status.success() {
+            return Err(SubmitError::Http(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+pub struct TcpClient;
+
+impl HttpClient for TcpClient {
+    fn post_form(
+        &self,
+        url: &str,
+        session: &str,
+        form: &[(&str, &str)],
+    ) -> Result<String, SubmitError> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| SubmitError::Http(format!("not a plain http url: {}", url)))?;
+        let (host, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let body = form
+            .iter()
+            .map(|(key, value)| format!("{}={}", encode(key), encode(value)))
+            .collect::<Vec<String>>()
+            .join("&");
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}\r\nCookie: session={}\r\n\
+             Content-Type: application/x-www-form-urlencoded\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{}",
+            path,
+            host,
+            USER_AGENT,
+            session,
+            body.len(),
+            body
+        );
+
+        let http_error = |e: std::io::Error| SubmitError::Http(format!("{}: {}", host, e));
+        let mut stream = TcpStream::connect(host).map_err(http_error)?;
+        stream
+            .set_read_timeout(Some(Duration::from_secs(30)))
+            .map_err(http_error)?;
+        stream.write_all(request.as_bytes()).map_err(http_error)?;
+        let mut response: Vec<u8> = Vec::new();
+        stream.read_to_end(&mut response).map_err(http_error)?;
+        let response = String::from_utf8_lossy(&response);
+
+        let (head, body) = response
+            .split_once("\r\n\r\n")
+            .ok_or_else(|| SubmitError::Http("malformed response".to_string()))?;
+        let status = head.split_whitespace().nth(1).unwrap_or("");
+        if !status.starts_with('2') {
+            return Err(SubmitError::Http(format!("server answered {}", status)));
+        }
+        Ok(body.to_string())
+    }
+}
+
+/// application/x-www-form-urlencoded
+pub fn encode(text: &str) -> String {
+    let mut encoded = String::new();
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b' ' => encoded.push('+'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+pub fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded: Vec<u8> = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (b'+', _) => decoded.push(b' '),
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 2;
+            }
+            (byte, _) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockServer;
+    #[test]
+    fn check_curl_keeps_the_session_off_the_command_line() {
+        let server = MockServer::start(&[(1, 1, "142")], Duration::from_secs(60));
+        let url = format!("{}/2023/day/1/answer", server.url);
+        let form = [("level", "1"), ("answer", "142")];
+        let session = "53cr3t\"\\session";
+        let command = CurlClient::command(&url, &form);
+        assert!(command
+            .get_args()
+            .all(|arg| !arg.to_string_lossy().contains("53cr3t")));
+        // the mock server turns away requests without a session cookie
+        let page = CurlClient.post_form(&url, session, &form).unwrap();
+        assert!(page.contains("That's the right answer!"));
+    }
+}
@@ -0,0 +1,373 @@
+// Helper for the days: `aoc submit <day> <part>` runs the day's solver (or takes
+// `--answer`), posts the answer and records what the server said in the
+// answers registry. `aoc mock <day><part>=<answer>...` runs the local stand-in
+// for the site.
+//
+//     AOC_SESSION=<cookie> cargo run -- submit 1 a
+//     cargo run -- submit 1 a --answer 142 --set server=http://127.0.0.1:8080
+mod client;
+mod mock;
+mod registry;
+mod response;
+
+use client::{client_for, HttpClient};
+use common::config::Config;
+use common::error::InputError;
+use mock::MockServer;
+use registry::Registry;
+use response::{parse_response, Outcome, Verdict};
+use std::{
+    env, fmt,
+    path::Path,
+    process::Command,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    A,
+    B,
+}
+
+impl Part {
+    fn parse(text: &str) -> Result<Part, SubmitError> {
+        match text {
+            "a" | "A" | "1" => Ok(Part::A),
+            "b" | "B" | "2" => Ok(Part::B),
+            _ => Err(SubmitError::Usage(format!("unknown part `{}`", text))),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Part::A => "a",
+            Part::B => "b",
+        }
+    }
+
+    fn level(&self) -> u32 {
+        match self {
+            Part::A => 1,
+            Part::B => 2,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SubmitError {
+    Usage(String),
+    /// The day's solver could not be run or printed no answer.
+    Solver(String),
+    Http(String),
+    /// Not sent, the registry already knows how it would go.
+    Refused(String),
+    Registry(String),
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SubmitError::Usage(m) => write!(f, "{}", m),
+            SubmitError::Solver(m) => write!(f, "solver: {}", m),
+            SubmitError::Http(m) => write!(f, "http: {}", m),
+            SubmitError::Refused(m) => write!(f, "not submitted: {}", m),
+            SubmitError::Registry(m) => write!(f, "registry: {}", m),
+        }
+    }
+}
+
+// Bad settings in config.toml or `--set`.
+impl From<InputError> for SubmitError {
+    fn from(error: InputError) -> SubmitError {
+        SubmitError::Usage(error.to_string())
+    }
+}
+
+pub struct Site {
+    pub server: String,
+    pub year: u32,
+    pub session: String,
+}
+
+/// Posts `answer` unless the registry rules it out, then records the outcome.
+pub fn submit(
+    client: &dyn HttpClient,
+    site: &Site,
+    registry: &mut Registry,
+    day: u32,
+    part: Part,
+    answer: &str,
+    now: u64,
+) -> Result<Outcome, SubmitError> {
+    if let Some(reason) = registry.check(day, part, answer, now) {
+        return Err(SubmitError::Refused(reason));
+    }
+    let url = format!("{}/{}/day/{}/answer", site.server, site.year, day);
+    let level = part.level().to_string();
+    let page = client.post_form(
+        &url,
+        &site.session,
+        &[("level", level.as_str()), ("answer", answer)],
+    )?;
+    let outcome = parse_response(&page);
+    registry.record(day, part, answer, &outcome, now);
+    Ok(outcome)
+}
+
+// Day 25 part B is the free last star, there is no answer to send for it.
+fn has_answer(day: u32, part: Part) -> bool {
+    !(day == 25 && part == Part::B)
+}
+
+// The answer in a day's stdout, from its `Part X result: ...` line.
+fn parse_answer(stdout: &str, part: Part) -> Option<String> {
+    let prefix = format!("Part {} result: ", part.name().to_uppercase());
+    stdout
+        .lines()
+        .find_map(|line| line.strip_prefix(&prefix).map(|a| a.trim().to_string()))
+}
+
+// Runs the day's binary from its own directory, like `cargo run` by hand, and
+// picks the answer out of what it prints.
+fn run_solver(day: u32, part: Part) -> Result<String, SubmitError> {
+    let directory = format!("../aoc{:02}", day);
+    let output = Command::new("cargo")
+        .args(["run", "--release", "--quiet"])
+        .current_dir(&directory)
+        .output()
+        .map_err(|e| SubmitError::Solver(format!("{}: {}", directory, e)))?;
+    if !output.status.success() {
+        return Err(SubmitError::Solver(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    parse_answer(&String::from_utf8_lossy(&output.stdout), part).ok_or_else(|| {
+        SubmitError::Solver(format!(
+            "{} printed no `Part {} result:` line",
+            directory,
+            part.name().to_uppercase()
+        ))
+    })
+}
+
+fn describe(verdict: Verdict) -> &'static str {
+    match verdict {
+        Verdict::Correct => "correct",
+        Verdict::TooHigh => "too high",
+        Verdict::TooLow => "too low",
+        Verdict::Incorrect => "wrong",
+        Verdict::TooRecent => "not checked, submitted too recently",
+        Verdict::WrongLevel => "not checked, level already solved or locked",
+        Verdict::Unrecognized => "unrecognized response",
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// Arguments that are not flags, skipping the values of the flags that take one.
+fn positional(args: &[String]) -> Vec<&str> {
+    let mut result: Vec<&str> = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--config" | "--set" | "--answer" | "--timeout" => i += 1,
+            arg => result.push(arg),
+        }
+        i += 1;
+    }
+    result
+}
+
+fn flag<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .map(|v| v.as_str())
+}
+
+fn run_submit(args: &[String], config: &Config) -> Result<(), SubmitError> {
+    let usage = || SubmitError::Usage("usage: aoc submit <day> <a|b> [--answer <value>]".into());
+    let (day, part) = match positional(args)[..] {
+        [_, day, part] => (day, part),
+        _ => return Err(usage()),
+    };
+    let day = day
+        .parse::<u32>()
+        .ok()
+        .filter(|d| (1..=25).contains(d))
+        .ok_or_else(usage)?;
+    let part = Part::parse(part)?;
+    if !has_answer(day, part) {
+        return Err(SubmitError::Refused(
+            "day 25 part B has no answer, the star comes with the other 49".into(),
+        ));
+    }
+    let answer = match flag(args, "--answer") {
+        Some(answer) => answer.to_string(),
+        None => run_solver(day, part)?,
+    };
+    let site = Site {
+        server: config.get("server", "https://adventofcode.com".to_string())?,
+        year: config.get("year", 2023)?,
+        session: env::var("AOC_SESSION")
+            .map_err(|_| SubmitError::Usage("set AOC_SESSION to the session cookie".into()))?,
+    };
+    let path = config.get("registry", "../answers.toml".to_string())?;
+    let mut registry = Registry::load(Path::new(&path))?;
+
+    let client = client_for(&site.server);
+    let outcome = submit(
+        client.as_ref(),
+        &site,
+        &mut registry,
+        day,
+        part,
+        &answer,
+        unix_time(),
+    )?;
+    registry.save(Path::new(&path))?;
+    println!(
+        "Day {} part {}: {} is {}",
+        day,
+        part.name().to_uppercase(),
+        answer,
+        describe(outcome.verdict)
+    );
+    if outcome.wait > 0 {
+        println!("Next answer accepted in {}s", outcome.wait);
+    }
+    if outcome.verdict == Verdict::Unrecognized {
+        println!("{}", outcome.message);
+    }
+    Ok(())
+}
+
+// aoc mock 1a=142 1b=281 [--timeout <seconds>]
+fn run_mock(args: &[String]) -> Result<(), SubmitError> {
+    let mut answers: Vec<(u32, u32, &str)> = Vec::new();
+    for spec in &positional(args)[1..] {
+        let parsed = spec.split_once('=').and_then(|(puzzle, answer)| {
+            let split = puzzle.len().checked_sub(1)?;
+            let (day, part) = (puzzle.get(..split)?, puzzle.get(split..)?);
+            Some((
+                day.parse::<u32>().ok()?,
+                Part::parse(part).ok()?.level(),
+                answer,
+            ))
+        });
+        answers.push(parsed.ok_or_else(|| {
+            SubmitError::Usage(format!("expected <day><a|b>=<answer>, found `{}`", spec))
+        })?);
+    }
+    let timeout = flag(args, "--timeout")
+        .map(|t| t.parse::<u64>())
+        .unwrap_or(Ok(60))
+        .map_err(|_| SubmitError::Usage("--timeout takes seconds".into()))?;
+    let server = MockServer::start(&answers, Duration::from_secs(timeout));
+    println!("Mock server listening on {}", server.url);
+    loop {
+        thread::park();
+    }
+}
+
+fn main() -> Result<(), SubmitError> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let config = Config::from_args("./config.toml", args.clone().into_iter())?;
+    match positional(&args).first() {
+        Some(&"submit") => run_submit(&args, &config),
+        Some(&"mock") => run_mock(&args),
+        _ => Err(SubmitError::Usage(
+            "usage: aoc submit <day> <a|b> | aoc mock <day><a|b>=<answer>...".into(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use client::TcpClient;
+    fn site(server: &MockServer) -> Site {
+        Site {
+            server: server.url.clone(),
+            year: 2023,
+            session: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn check_submit_against_mock_server() {
+        let server = MockServer::start(&[(1, 1, "142"), (1, 2, "281")], Duration::from_secs(60));
+        let site = site(&server);
+        let mut registry = Registry::default();
+
+        let outcome = submit(&TcpClient, &site, &mut registry, 1, Part::A, "500", 1000).unwrap();
+        assert_eq!((Verdict::TooHigh, 60), (outcome.verdict, outcome.wait));
+        // known to be too high, never reaches the server
+        let refused = submit(&TcpClient, &site, &mut registry, 1, Part::A, "600", 2000);
+        assert!(matches!(refused, Err(SubmitError::Refused(_))));
+        assert_eq!(1, server.requests());
+
+        // the server still enforces its timeout on a registry that does not know about it
+        let mut fresh = Registry::default();
+        let outcome = submit(&TcpClient, &site, &mut fresh, 1, Part::A, "142", 1000).unwrap();
+        assert_eq!(Verdict::TooRecent, outcome.verdict);
+        assert!(outcome.wait > 0);
+
+        let outcome = submit(&TcpClient, &site, &mut registry, 1, Part::B, "281", 1000).unwrap();
+        assert_eq!(Verdict::Correct, outcome.verdict);
+        assert_eq!(Some("281".to_string()), registry.entry(1, Part::B).correct);
+        let again = submit(&TcpClient, &site, &mut registry, 1, Part::B, "281", 5000);
+        assert!(matches!(again, Err(SubmitError::Refused(_))));
+        assert_eq!(3, server.requests());
+    }
+
+    #[test]
+    fn check_every_day_prints_a_parsable_answer() {
+        for day in 1..=25 {
+            let source = std::fs::read_to_string(format!("../aoc{:02}/src/main.rs", day)).unwrap();
+            // what the day's println!s print, with 42 for every value
+            let stdout = source
+                .lines()
+                .filter_map(|line| line.trim().strip_prefix("println!(\""))
+                .filter_map(|line| line.split_once('"'))
+                .map(|(format, _)| format.replace("{}", "42"))
+                .collect::<Vec<String>>()
+                .join("\n");
+            // every part the day solves, day 24 has no part B solver yet
+            let solved = |part: &Part| source.contains(&format!("fn solve_part_{}(", part.name()));
+            for part in [Part::A, Part::B]
+                .into_iter()
+                .filter(|p| has_answer(day, *p) && solved(p))
+            {
+                assert_eq!(
+                    Some("42".to_string()),
+                    parse_answer(&stdout, part),
+                    "day {} part {}",
+                    day,
+                    part.name()
+                );
+            }
+        }
+        assert_eq!(None, parse_answer("Part B -> Press the button", Part::B));
+    }
+
+    #[test]
+    fn check_solved_level_and_low_answers() {
+        let server = MockServer::start(&[(7, 1, "6440")], Duration::from_secs(0));
+        let site = site(&server);
+        let mut registry = Registry::default();
+        let outcome = submit(&TcpClient, &site, &mut registry, 7, Part::A, "10", 1000).unwrap();
+        assert_eq!(Verdict::TooLow, outcome.verdict);
+        let outcome = submit(&TcpClient, &site, &mut registry, 7, Part::A, "6440", 1000).unwrap();
+        assert_eq!(Verdict::Correct, outcome.verdict);
+        let mut fresh = Registry::default();
+        let outcome = submit(&TcpClient, &site, &mut fresh, 7, Part::A, "6440", 1000).unwrap();
+        assert_eq!(Verdict::WrongLevel, outcome.verdict);
+    }
+}
@@ -0,0 +1,173 @@
+// A stand-in for adventofcode.com that answers `POST /<year>/day/<day>/answer`
+// the way the real site does, so the client, the response parser and the
+// registry can be exercised without a session cookie or network access.
+//
+// It knows the right answer per (day, level), says too high/too low for wrong
+// numbers and enforces the timeout after a wrong answer.
+use crate::client::decode;
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+pub struct MockServer {
+    pub url: String,
+    #[allow(dead_code)] // only the tests look at it
+    requests: Arc<AtomicUsize>,
+}
+
+struct State {
+    answers: HashMap<(u32, u32), String>,
+    solved: Vec<(u32, u32)>,
+    blocked_until: HashMap<(u32, u32), Instant>,
+    timeout: Duration,
+}
+
+impl MockServer {
+    /// Serves `answers` as (day, level, answer) on a free local port. A wrong
+    /// answer blocks that puzzle for `timeout`.
+    pub fn start(answers: &[(u32, u32, &str)], timeout: Duration) -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("mock server cannot bind");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let state = Arc::new(Mutex::new(State {
+            answers: answers
+                .iter()
+                .map(|(day, level, answer)| ((*day, *level), answer.to_string()))
+                .collect(),
+            solved: Vec::new(),
+            blocked_until: HashMap::new(),
+            timeout,
+        }));
+
+        let counter = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let _ = handle(stream, &state);
+            }
+        });
+        MockServer { url, requests }
+    }
+
+    /// Number of requests that reached the server so far.
+    #[allow(dead_code)]
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+}
+
+fn handle(stream: TcpStream, state: &Mutex<State>) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut content_length = 0;
+    let mut has_session = false;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            match name.to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.trim().parse().unwrap_or(0),
+                "cookie" => has_session = value.trim().starts_with("session="),
+                _ => (),
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    let form = String::from_utf8_lossy(&body)
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (decode(key), decode(value)))
+        .collect::<HashMap<String, String>>();
+
+    let day = request_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|path| path.split('/').nth(3))
+        .and_then(|day| day.parse::<u32>().ok());
+    let level = form.get("level").and_then(|l| l.parse::<u32>().ok());
+    let (status, message) = match (day, level, form.get("answer"), has_session) {
+        (_, _, _, false) => (
+            "400 Bad Request",
+            "Puzzle inputs differ by user.  Please log in to get your puzzle input.".to_string(),
+        ),
+        (Some(day), Some(level), Some(answer), true) => (
+            "200 OK",
+            judge(&mut state.lock().unwrap(), day, level, answer),
+        ),
+        _ => ("404 Not Found", "404 Not Found".to_string()),
+    };
+    let page = format!(
+        "<!DOCTYPE html>\n<html><body><main>\n<article><p>{}</p></article>\n</main></body></html>\n",
+        message
+    );
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        page.len(),
+        page
+    )
+}
+
+fn judge(state: &mut State, day: u32, level: u32, answer: &str) -> String {
+    let puzzle = (day, level);
+    let correct = match state.answers.get(&puzzle) {
+        Some(correct) if !state.solved.contains(&puzzle) => correct.clone(),
+        _ => {
+            return "You don't seem to be solving the right level.  Did you already complete it? \
+                    <a href=\"/2023/day/1\">[Return to Day 1]</a>"
+                .to_string()
+        }
+    };
+    let now = Instant::now();
+    if let Some(until) = state
+        .blocked_until
+        .get(&puzzle)
+        .filter(|until| **until > now)
+    {
+        let left = (*until - now).as_secs() + 1;
+        return format!(
+            "You gave an answer too recently; you have to wait after submitting an answer \
+             before trying again.  You have {}m {}s left to wait.",
+            left / 60,
+            left % 60
+        );
+    }
+    if answer == correct {
+        state.solved.push(puzzle);
+        return "That's the right answer!  You are <span class=\"day-success\">one gold star</span> \
+                closer to restoring snow operations."
+            .to_string();
+    }
+    state.blocked_until.insert(puzzle, now + state.timeout);
+    let hint = match (answer.parse::<i128>(), correct.parse::<i128>()) {
+        (Ok(a), Ok(c)) if a > c => "; your answer is too high",
+        (Ok(a), Ok(c)) if a < c => "; your answer is too low",
+        _ => "",
+    };
+    format!(
+        "That's not the right answer{}.  If you're stuck, make sure you're using the full input \
+         data; please wait {} before trying again.",
+        hint,
+        match state.timeout.as_secs() {
+            60 => "one minute".to_string(),
+            s if s % 60 == 0 => format!("{} minutes", s / 60),
+            s => format!("{} seconds", s),
+        }
+    )
+}
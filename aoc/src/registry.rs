@@ -0,0 +1,224 @@
+// Everything the server has told us about our answers, one `[dayNN.part]`
+// section per puzzle:
+//
+//     [day01.a]
+//     correct = "142"
+//     too_high = "500"
+//     wrong = "12,100"
+//     wait_until = 1701410000
+//
+// It is read back with the same TOML subset as the per-day config and lets
+// `aoc submit` refuse answers that are already known to be wrong before they
+// cost another timeout.
+use crate::{
+    response::{Outcome, Verdict},
+    Part, SubmitError,
+};
+use common::config::Config;
+use common::error::InputError;
+use std::{cmp::Ordering, collections::BTreeMap, fs, path::Path};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Entry {
+    pub correct: Option<String>,
+    /// Lowest answer the server called too high.
+    pub too_high: Option<String>,
+    /// Highest answer the server called too low.
+    pub too_low: Option<String>,
+    /// Every rejected answer, in the order they were sent.
+    pub wrong: Vec<String>,
+    /// Unix time before which the server will not look at another answer.
+    pub wait_until: u64,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Registry {
+    entries: BTreeMap<(u32, Part), Entry>,
+}
+
+impl Registry {
+    pub fn parse(text: &str) -> Result<Registry, SubmitError> {
+        let config = Config::parse(text).map_err(|e| SubmitError::Registry(e.to_string()))?;
+        let mut registry = Registry::default();
+        for day in 1..=25 {
+            for part in [Part::A, Part::B] {
+                let key = |name: &str| format!("{}.{}", section(day, part), name);
+                let invalid = |e: InputError| SubmitError::Registry(e.to_string());
+                let text = |name: &str| {
+                    config
+                        .get::<String>(&key(name), String::new())
+                        .map(|value| Some(value).filter(|value| !value.is_empty()))
+                        .map_err(invalid)
+                };
+                let entry = Entry {
+                    correct: text("correct")?,
+                    too_high: text("too_high")?,
+                    too_low: text("too_low")?,
+                    wrong: text("wrong")?
+                        .map(|w| w.split(',').map(|s| s.to_string()).collect())
+                        .unwrap_or_default(),
+                    wait_until: config.get(&key("wait_until"), 0).map_err(invalid)?,
+                };
+                if entry != Entry::default() {
+                    registry.entries.insert((day, part), entry);
+                }
+            }
+        }
+        Ok(registry)
+    }
+
+    // A registry that does not exist yet is empty.
+    pub fn load(path: &Path) -> Result<Registry, SubmitError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Registry::parse(&text),
+            Err(_) => Ok(Registry::default()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SubmitError> {
+        fs::write(path, self.to_string())
+            .map_err(|e| SubmitError::Registry(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn entry(&self, day: u32, part: Part) -> Entry {
+        self.entries.get(&(day, part)).cloned().unwrap_or_default()
+    }
+
+    /// Why `answer` should not be sent at unix time `now`, if anything speaks
+    /// against it.
+    pub fn check(&self, day: u32, part: Part, answer: &str, now: u64) -> Option<String> {
+        let entry = self.entry(day, part);
+        if let Some(correct) = &entry.correct {
+            return Some(if correct == answer {
+                format!("{} is already the accepted answer", answer)
+            } else {
+                format!("the accepted answer is {}, not {}", correct, answer)
+            });
+        }
+        if entry.wrong.iter().any(|w| w == answer) {
+            return Some(format!("{} was already rejected", answer));
+        }
+        let value = answer.parse::<i128>().ok();
+        let bound = |b: &Option<String>| b.as_ref().and_then(|b| b.parse::<i128>().ok());
+        if let (Some(value), Some(high)) = (value, bound(&entry.too_high)) {
+            if value >= high {
+                return Some(format!("{} is too high, {} already was", answer, high));
+            }
+        }
+        if let (Some(value), Some(low)) = (value, bound(&entry.too_low)) {
+            if value <= low {
+                return Some(format!("{} is too low, {} already was", answer, low));
+            }
+        }
+        if entry.wait_until > now {
+            return Some(format!(
+                "the server is not accepting answers for another {}s",
+                entry.wait_until - now
+            ));
+        }
+        None
+    }
+
+    pub fn record(&mut self, day: u32, part: Part, answer: &str, outcome: &Outcome, now: u64) {
+        let entry = self.entries.entry((day, part)).or_default();
+        entry.wait_until = now + outcome.wait;
+        match outcome.verdict {
+            Verdict::Correct => entry.correct = Some(answer.to_string()),
+            Verdict::TooHigh => {
+                entry.wrong.push(answer.to_string());
+                if tightens(&entry.too_high, answer, Ordering::Less) {
+                    entry.too_high = Some(answer.to_string());
+                }
+            }
+            Verdict::TooLow => {
+                entry.wrong.push(answer.to_string());
+                if tightens(&entry.too_low, answer, Ordering::Greater) {
+                    entry.too_low = Some(answer.to_string());
+                }
+            }
+            Verdict::Incorrect => entry.wrong.push(answer.to_string()),
+            Verdict::TooRecent | Verdict::WrongLevel | Verdict::Unrecognized => (),
+        }
+    }
+}
+
+// Whether `answer` lies on the `direction` side of the current bound.
+fn tightens(bound: &Option<String>, answer: &str, direction: Ordering) -> bool {
+    match (bound, answer.parse::<i128>()) {
+        (Some(old), Ok(new)) => match old.parse::<i128>() {
+            Ok(old) => new.cmp(&old) == direction,
+            Err(_) => true,
+        },
+        _ => true,
+    }
+}
+
+fn section(day: u32, part: Part) -> String {
+    format!("day{:02}.{}", day, part.name())
+}
+
+impl std::fmt::Display for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "# Answers sent with `aoc submit` and what the server said."
+        )?;
+        for ((day, part), entry) in &self.entries {
+            writeln!(f, "\n[{}]", section(*day, *part))?;
+            let fields = [
+                ("correct", entry.correct.clone()),
+                ("too_high", entry.too_high.clone()),
+                ("too_low", entry.too_low.clone()),
+                (
+                    "wrong",
+                    Some(entry.wrong.join(",")).filter(|w| !w.is_empty()),
+                ),
+            ];
+            for (name, value) in fields {
+                if let Some(value) = value {
+                    writeln!(f, "{} = \"{}\"", name, value)?;
+                }
+            }
+            if entry.wait_until > 0 {
+                writeln!(f, "wait_until = {}", entry.wait_until)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn outcome(verdict: Verdict, wait: u64) -> Outcome {
+        Outcome {
+            verdict,
+            wait,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn check_bounds_and_round_trip() {
+        let mut registry = Registry::default();
+        registry.record(1, Part::A, "500", &outcome(Verdict::TooHigh, 60), 1000);
+        registry.record(1, Part::A, "900", &outcome(Verdict::TooHigh, 60), 1000);
+        registry.record(1, Part::A, "7", &outcome(Verdict::TooLow, 60), 1000);
+        assert_eq!(Some("500".to_string()), registry.entry(1, Part::A).too_high);
+        assert!(registry.check(1, Part::A, "600", 2000).is_some());
+        assert!(registry.check(1, Part::A, "7", 2000).is_some());
+        assert!(registry.check(1, Part::A, "142", 1030).is_some());
+        assert_eq!(None, registry.check(1, Part::A, "142", 2000));
+
+        registry.record(1, Part::A, "142", &outcome(Verdict::Correct, 0), 2000);
+        let reloaded = Registry::parse(&registry.to_string()).unwrap();
+        assert_eq!(registry, reloaded);
+        assert_eq!(vec!["500", "900", "7"], reloaded.entry(1, Part::A).wrong);
+    }
+
+    #[test]
+    fn check_missing_registry_is_empty() {
+        let registry = Registry::load(Path::new("./does-not-exist.toml")).unwrap();
+        assert_eq!(Entry::default(), registry.entry(5, Part::B));
+    }
+}
@@ -0,0 +1,137 @@
+// Reads the verdict out of the HTML page adventofcode.com answers a submission
+// with. Only the sentences inside <article> matter, everything else on the page
+// is navigation.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without a hint in which direction.
+    Incorrect,
+    /// Submitted before the previous timeout ran out, the answer was not checked.
+    TooRecent,
+    /// The level was already solved (or is not unlocked yet).
+    WrongLevel,
+    Unrecognized,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub verdict: Verdict,
+    /// Seconds until the next answer for this puzzle will be accepted.
+    pub wait: u64,
+    pub message: String,
+}
+
+pub fn parse_response(html: &str) -> Outcome {
+    let message = article_text(html);
+    let verdict = if message.contains("That's the right answer") {
+        Verdict::Correct
+    } else if message.contains("answer too recently") {
+        Verdict::TooRecent
+    } else if message.contains("solving the right level") {
+        Verdict::WrongLevel
+    } else if message.contains("That's not the right answer") {
+        if message.contains("too high") {
+            Verdict::TooHigh
+        } else if message.contains("too low") {
+            Verdict::TooLow
+        } else {
+            Verdict::Incorrect
+        }
+    } else {
+        Verdict::Unrecognized
+    };
+    Outcome {
+        verdict,
+        wait: parse_wait(&message),
+        message,
+    }
+}
+
+// "You have 5m 12s left to wait." after submitting too early, "please wait one
+// minute before trying again" (or "5 minutes") after a wrong answer.
+fn parse_wait(message: &str) -> u64 {
+    if let Some(start) = message.find("You have ") {
+        let rest = &message[start + "You have ".len()..];
+        if let Some(end) = rest.find(" left to wait") {
+            return rest[..end]
+                .split_whitespace()
+                .map(|part| {
+                    let digits = part.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+                    let value = digits.parse::<u64>().unwrap_or(0);
+                    match &part[digits.len()..] {
+                        "h" => value * 3600,
+                        "m" => value * 60,
+                        _ => value,
+                    }
+                })
+                .sum();
+        }
+    }
+    if let Some(start) = message.find("please wait ") {
+        let mut words = message[start + "please wait ".len()..].split_whitespace();
+        let amount = match words.next() {
+            Some("one") | Some("a") => 1,
+            Some(word) => word.parse::<u64>().unwrap_or(0),
+            None => 0,
+        };
+        return match words.next() {
+            Some(unit) if unit.starts_with("minute") => amount * 60,
+            Some(unit) if unit.starts_with("second") => amount,
+            _ => 0,
+        };
+    }
+    0
+}
+
+// The text of the <article> element with tags stripped, or the whole page if
+// there is none.
+fn article_text(html: &str) -> String {
+    let body = match (html.find("<article"), html.find("</article>")) {
+        (Some(start), Some(end)) if start < end => &html[start..end],
+        _ => html,
+    };
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in body.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => (),
+        }
+    }
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn check_verdicts() {
+        let wrong = "<main><article><p>That's not the right answer; your answer is too high. \
+            If you're stuck, make sure you're using the full input data; please wait one \
+            minute before trying again. <a href=\"/2023/day/1\">[Return to Day 1]</a></p></article></main>";
+        let outcome = parse_response(wrong);
+        assert_eq!((Verdict::TooHigh, 60), (outcome.verdict, outcome.wait));
+
+        let right = "<article><p>That's the <em>right answer</em>! You are one gold star closer.</p></article>";
+        assert_eq!(Verdict::Correct, parse_response(right).verdict);
+
+        let done = "<article><p>You don't seem to be solving the right level.  Did you already complete it?</p></article>";
+        assert_eq!(Verdict::WrongLevel, parse_response(done).verdict);
+    }
+
+    #[test]
+    fn check_wait_times() {
+        let early = "<article><p>You gave an answer too recently; you have to wait after \
+            submitting an answer before trying again.  You have 5m 12s left to wait.</p></article>";
+        let outcome = parse_response(early);
+        assert_eq!((Verdict::TooRecent, 312), (outcome.verdict, outcome.wait));
+        let outcome =
+            parse_response("<article>That's not the right answer. please wait 5 minutes</article>");
+        assert_eq!((Verdict::Incorrect, 300), (outcome.verdict, outcome.wait));
+    }
+}
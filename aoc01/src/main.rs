@@ -1,102 +1,241 @@
-use std::collections::HashMap;
-use std::fs::read_to_string;
+use common::aho_corasick::AhoCorasick;
+use common::config::Config;
+use common::error::{overflow, InputError};
+use common::input::read_input;
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
+fn allowed(c: char) -> bool {
+    c.is_alphanumeric()
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
+}
+
+const ENGLISH: &str = "one=1,two=2,three=3,four=4,five=5,six=6,seven=7,eight=8,nine=9";
+
+// 0 to 9 written as digits, part of every vocabulary
+fn digit_tokens() -> Vec<(String, u32)> {
+    (0..=9).map(|d| (d.to_string(), d)).collect()
+}
+
+// Every spelling that counts as a digit, searched for all at once.
+struct Vocabulary {
+    automaton: AhoCorasick,
+    values: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Token {
+    value: u32,
+    // byte range in the line
+    start: usize,
+    end: usize,
+}
+
+impl Vocabulary {
+    fn new(tokens: &[(String, u32)]) -> Vocabulary {
+        Vocabulary {
+            automaton: AhoCorasick::new(
+                &tokens
+                    .iter()
+                    .map(|(t, _)| t.as_str())
+                    .collect::<Vec<&str>>(),
+            ),
+            values: tokens.iter().map(|(_, v)| *v).collect(),
+        }
+    }
+
+    // Part A, plain digits only.
+    fn digits() -> Vocabulary {
+        Vocabulary::new(&digit_tokens())
+    }
+
+    // The plain digits plus `words`, a list of `word=digit` pairs such as
+    // `one=1,two=2` or `eins=1,zwei=2`. A word may also be an alias like `l=1`.
+    fn parse(words: &str) -> Result<Vocabulary, InputError> {
+        let mut tokens = digit_tokens();
+        for pair in words.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let token = pair
+                .split_once('=')
+                .map(|(word, digit)| (word.trim(), digit.trim()))
+                .filter(|(word, _)| !word.is_empty())
+                .and_then(|(word, digit)| Some((word.to_string(), digit.parse::<u32>().ok()?)))
+                .filter(|(_, digit)| *digit <= 9)
+                .ok_or_else(|| {
+                    InputError::new(format!(
+                        "expected `word=digit` with a digit 0-9, found `{}`",
+                        pair
+                    ))
+                })?;
+            tokens.push(token);
+        }
+        Ok(Vocabulary::new(&tokens))
+    }
+
+    // All tokens in `line`, overlapping ones included: `oneight` has both
+    // `one` and `eight`.
+    fn scan(&self, line: &str) -> Vec<Token> {
+        self.automaton
+            .find_overlapping(line)
+            .iter()
+            .map(|m| Token {
+                value: self.values[m.pattern],
+                start: m.start,
+                end: m.end,
+            })
+            .collect()
     }
-    result
 }
 
-fn solve_part_a(input: &Vec<String>) -> i32 {
-    input
-        .iter()
-        .map(|line| {
-            let mut first_character: String = String::new();
-            let mut last_character: String = String::new();
-            for c in line.chars() {
-                if c.is_numeric() {
-                    first_character = c.to_string();
-                    break;
-                }
-            }
-            for c in line.chars().rev() {
-                if c.is_numeric() {
-                    last_character = c.to_string();
-                    break;
-                }
-            }
-            (first_character + &last_character).parse::<i32>().unwrap()
-        })
-        .collect::<Vec<i32>>()
-        .iter()
-        .sum()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Calibration {
+    first: Token,
+    last: Token,
+    value: u32,
+}
+
+// First digit is the token starting first, last digit the one ending last.
+// Ties go to the longer token.
+fn calibrate(vocabulary: &Vocabulary, index: usize, line: &str) -> Result<Calibration, InputError> {
+    let tokens = vocabulary.scan(line);
+    let first = tokens.iter().min_by_key(|t| (t.start, usize::MAX - t.end));
+    let last = tokens.iter().max_by_key(|t| (t.end, usize::MAX - t.start));
+    match (first, last) {
+        (Some(first), Some(last)) => Ok(Calibration {
+            first: *first,
+            last: *last,
+            value: first.value * 10 + last.value,
+        }),
+        _ => Err(InputError::at(index, "line has no digit")),
+    }
 }
 
-fn solve_part_b(input: &Vec<String>) -> i32 {
+fn calibration_report(
+    input: &[String],
+    vocabulary: &Vocabulary,
+) -> Result<Vec<Calibration>, InputError> {
     input
         .iter()
-        .map(|line| {
-            let reversed_line = line.clone().chars().rev().collect::<String>();
-            let patterns = [
-                "1", "one", "2", "two", "3", "three", "4", "four", "5", "five", "6", "six", "7",
-                "seven", "8", "eight", "9", "nine",
-            ];
-            let map = patterns
-                .into_iter()
-                .enumerate()
-                .map(|(i, pattern)| {
-                    let index: i32 = match line.find(pattern) {
-                        Some(n) => n as i32,
-                        None => i32::MAX,
-                    };
-                    (index, (i as i32 / 2) + 1)
-                })
-                .collect::<HashMap<i32, i32>>();
-            let first_character: String = (map.iter().min_by_key(|s| *s).unwrap().1).to_string();
-
-            let map = patterns
-                .into_iter()
-                .enumerate()
-                .map(|(i, pattern)| {
-                    let index: i32 =
-                        match reversed_line.find(&pattern.chars().rev().collect::<String>()) {
-                            Some(n) => n as i32,
-                            None => i32::MAX,
-                        };
-                    (index, (i as i32 / 2) + 1)
-                })
-                .collect::<HashMap<i32, i32>>();
-            let last_character: String = (map.iter().min_by_key(|s| *s).unwrap().1).to_string();
-
-            (first_character + &last_character).parse::<i32>().unwrap()
-        })
-        .collect::<Vec<i32>>()
+        .enumerate()
+        .map(|(i, line)| calibrate(vocabulary, i, line))
+        .collect()
+}
+
+// Tab separated, one row per input line, so it can be pasted into a
+// spreadsheet or diffed between two vocabularies. Positions are byte offsets.
+fn report_table(input: &[String], report: &[Calibration]) -> String {
+    let mut table = String::from("line\tfirst\tfirst_at\tlast\tlast_at\tvalue\ttext\n");
+    for (i, (line, c)) in input.iter().zip(report).enumerate() {
+        table += &format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            i + 1,
+            &line[c.first.start..c.first.end],
+            c.first.start,
+            &line[c.last.start..c.last.end],
+            c.last.start,
+            c.value,
+            line
+        );
+    }
+    table
+}
+
+fn solve(input: &[String], vocabulary: &Vocabulary) -> Result<u32, InputError> {
+    calibration_report(input, vocabulary)?
         .iter()
-        .sum()
+        .try_fold(0u32, |sum, c| sum.checked_add(c.value))
+        .ok_or_else(overflow)
+}
+
+fn solve_part_a(input: &Vec<String>) -> Result<u32, InputError> {
+    solve(input, &Vocabulary::digits())
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: i32 = solve_part_a(&input);
-    let result_part_b: i32 = solve_part_b(&input);
-    println!("result of part a {}", result_part_a);
-    println!("result of part b {}", result_part_b);
+fn solve_part_b(input: &Vec<String>, vocabulary: &Vocabulary) -> Result<u32, InputError> {
+    solve(input, vocabulary)
+}
+
+// `cargo run -- --report [a|b]` prints the per line calibration table instead
+// of the totals, part B unless `a` is given.
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = Config::from_args("./config.toml", args.clone().into_iter())?;
+    let vocabulary = Vocabulary::parse(&config.get("words", ENGLISH.to_string())?)?;
+    if let Some(i) = args.iter().position(|a| a == "--report") {
+        let vocabulary = match args.get(i + 1).map(|a| a.as_str()) {
+            Some("a") => Vocabulary::digits(),
+            _ => vocabulary,
+        };
+        print!(
+            "{}",
+            report_table(&input, &calibration_report(&input, &vocabulary)?)
+        );
+        return Ok(());
+    }
+    let result_part_a: u32 = solve_part_a(&input)?;
+    let result_part_b: u32 = solve_part_b(&input, &vocabulary)?;
+    println!("Part A result: {}", result_part_a);
+    println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example-a.txt");
-        assert_eq!(142, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example-a.txt").unwrap();
+        assert_eq!(Ok(142), solve_part_a(&example));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example-b.txt");
-        assert_eq!(281, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example-b.txt").unwrap();
+        assert_eq!(
+            Ok(281),
+            solve_part_b(&example, &Vocabulary::parse(ENGLISH).unwrap())
+        );
+    }
+
+    #[test]
+    fn check_overlapping_and_custom_words() {
+        let english = Vocabulary::parse(ENGLISH).unwrap();
+        let value = |v: &Vocabulary, line: &str| calibrate(v, 0, line).map(|c| c.value);
+        assert_eq!(Ok(18), value(&english, "oneight"));
+        assert_eq!(Ok(83), value(&english, "eightwothree"));
+        let german = Vocabulary::parse("null=0,eins=1,zwei=2,drei=3,fünf=5").unwrap();
+        assert_eq!(Ok(53), value(&german, "xfünfzweinullxdrei"));
+        assert_eq!(Ok(0), value(&german, "null"));
+        assert_eq!(Ok(9), value(&german, "0x9"));
+        assert_eq!(Ok(0), value(&english, "x0"));
+        assert!(Vocabulary::parse("ten=10").is_err());
+        assert!(value(&english, "abc").is_err());
+    }
+
+    #[test]
+    fn check_calibration_report() {
+        let input = vec!["eightwothree".to_string(), "xoneight".to_string()];
+        let report = calibration_report(&input, &Vocabulary::parse(ENGLISH).unwrap()).unwrap();
+        assert_eq!(
+            (0, 7, 83),
+            (report[0].first.start, report[0].last.start, report[0].value)
+        );
+        assert_eq!(
+            "line\tfirst\tfirst_at\tlast\tlast_at\tvalue\ttext\n\
+             1\teight\t0\tthree\t7\t83\teightwothree\n\
+             2\tone\t1\teight\t3\t18\txoneight\n",
+            report_table(&input, &report)
+        );
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        let english = Vocabulary::parse(ENGLISH).unwrap();
+        fuzz_lines(&["a1b2\nxtwone3four\n7\n"], allowed, move |input| {
+            let _ = solve_part_a(input);
+            let _ = solve_part_b(input, &english);
+        });
     }
 }
@@ -1,94 +1,480 @@
-use std::fs::read_to_string;
+use common::input::read_input;
+use common::{
+    config::Config,
+    error::{overflow, parse_number, InputError},
+};
+use std::collections::{BTreeMap, BTreeSet};
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
+fn allowed(c: char) -> bool {
+    c.is_ascii_alphanumeric() || " :,;".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
+}
+
+// color -> number of cubes
+type Cubes = BTreeMap<String, u64>;
+
+// One handful of cubes shown from the bag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Draw {
+    cubes: Cubes,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Game {
+    id: u64,
+    draws: Vec<Draw>,
+}
+
+impl Game {
+    // The fewest cubes of every color the bag must have held for this game.
+    fn minimum_bag(&self) -> Cubes {
+        let mut bag = Cubes::new();
+        for (color, count) in self.draws.iter().flat_map(|d| d.cubes.iter()) {
+            let most = bag.entry(color.clone()).or_insert(0);
+            *most = (*most).max(*count);
+        }
+        bag
+    }
+
+    // A color the bag does not list at all is a color it has none of.
+    fn is_possible(&self, bag: &Cubes) -> bool {
+        self.draws
+            .iter()
+            .flat_map(|d| d.cubes.iter())
+            .all(|(color, count)| bag.get(color).is_some_and(|available| count <= available))
     }
-    result
 }
 
-fn solve_part_a(input: &Vec<String>) -> i32 {
-    let red: i32 = 12;
-    let green: i32 = 13;
-    let blue: i32 = 14;
+// `3 blue, 4 red`
+fn parse_draw(index: usize, text: &str) -> Result<Draw, InputError> {
+    let mut cubes = Cubes::new();
+    for cube in text.split(',') {
+        let (count, color) = match cube.split_whitespace().collect::<Vec<&str>>()[..] {
+            [count, color] => (parse_number::<u64>(count, index)?, color),
+            _ => {
+                return Err(InputError::at(
+                    index,
+                    format!("malformed draw `{}`", cube.trim()),
+                ))
+            }
+        };
+        if cubes.insert(color.to_string(), count).is_some() {
+            return Err(InputError::at(
+                index,
+                format!("{} appears twice in the draw `{}`", color, text.trim()),
+            ));
+        }
+    }
+    Ok(Draw { cubes })
+}
+
+// `Game 12: 3 blue, 4 red; 1 red, 2 green`
+fn parse_game(index: usize, line: &str) -> Result<Game, InputError> {
+    let (name, draws) = line
+        .split_once(':')
+        .ok_or_else(|| InputError::at(index, "missing `:` after the game id"))?;
+    let id = match name.split_whitespace().collect::<Vec<&str>>()[..] {
+        ["Game", id] => parse_number::<u64>(id, index)?,
+        _ => return Err(InputError::at(index, "expected `Game <id>:`")),
+    };
+    Ok(Game {
+        id,
+        draws: draws
+            .split(';')
+            .map(|draw| parse_draw(index, draw))
+            .collect::<Result<Vec<Draw>, InputError>>()?,
+    })
+}
+
+fn parse_games(input: &[String]) -> Result<Vec<Game>, InputError> {
     input
         .iter()
         .enumerate()
-        .map(|(i, line)| {
-            let s = *line.split(":").collect::<Vec<&str>>().get(1).unwrap();
-            for draw in s.replace(";", ",").split(",") {
-                let seg = draw.split(" ").collect::<Vec<&str>>();
-                match *seg.get(2).unwrap() {
-                    "red" => {
-                        if seg.get(1).unwrap().parse::<i32>().unwrap() > red {
-                            return 0;
-                        }
-                    }
-                    "green" => {
-                        if seg.get(1).unwrap().parse::<i32>().unwrap() > green {
-                            return 0;
-                        }
-                    }
-                    "blue" => {
-                        if seg.get(1).unwrap().parse::<i32>().unwrap() > blue {
-                            return 0;
-                        }
-                    }
-                    _ => (),
-                }
+        .map(|(i, line)| parse_game(i, line))
+        .collect()
+}
+
+fn colors(games: &[Game]) -> BTreeSet<&String> {
+    games
+        .iter()
+        .flat_map(|game| game.draws.iter().flat_map(|d| d.cubes.keys()))
+        .collect()
+}
+
+fn possible_games<'a>(games: &'a [Game], bag: &Cubes) -> Vec<&'a Game> {
+    games.iter().filter(|game| game.is_possible(bag)).collect()
+}
+
+// The power multiplies the minimum count of every color seen anywhere in the
+// input, so a game that never shows one of them has power 0.
+fn power(bag: &Cubes, colors: &BTreeSet<&String>) -> Option<u64> {
+    colors.iter().try_fold(1u64, |power, color| {
+        power.checked_mul(*bag.get(*color).unwrap_or(&0))
+    })
+}
+
+// The bag with the fewest cubes in total that makes at least `k` games
+// possible, ties going to fewer cubes of the alphabetically first colors. Each
+// color's count in such a bag is the minimum of one of the games, so every
+// choice of those for all colors but the last is tried, and the last color
+// takes the k-th smallest minimum among the games still possible.
+fn smallest_bag(games: &[Game], k: usize) -> Option<Cubes> {
+    if k > games.len() {
+        return None;
+    }
+    let colors = colors(games).into_iter().collect::<Vec<&String>>();
+    if k == 0 || colors.is_empty() {
+        return Some(Cubes::new());
+    }
+    let minimums = games
+        .iter()
+        .map(|game| {
+            let bag = game.minimum_bag();
+            colors.iter().map(|c| *bag.get(*c).unwrap_or(&0)).collect()
+        })
+        .collect::<Vec<Vec<u64>>>();
+    let mut best: Option<(u64, Vec<u64>)> = None;
+    let candidates = (0..games.len()).collect::<Vec<usize>>();
+    search_bag(&minimums, k, &candidates, &mut Vec::new(), &mut best);
+    best.map(|(_, counts)| colors.iter().map(|c| c.to_string()).zip(counts).collect())
+}
+
+fn search_bag(
+    minimums: &[Vec<u64>],
+    k: usize,
+    candidates: &[usize],
+    chosen: &mut Vec<u64>,
+    best: &mut Option<(u64, Vec<u64>)>,
+) {
+    let color = chosen.len();
+    let total = chosen.iter().fold(0u64, |sum, c| sum.saturating_add(*c));
+    let mut thresholds = candidates
+        .iter()
+        .map(|g| minimums[*g][color])
+        .collect::<Vec<u64>>();
+    thresholds.sort();
+    if color + 1 == minimums[0].len() {
+        let last = thresholds[k - 1];
+        if best
+            .as_ref()
+            .is_none_or(|(b, _)| total.saturating_add(last) < *b)
+        {
+            chosen.push(last);
+            *best = Some((total.saturating_add(last), chosen.clone()));
+            chosen.pop();
+        }
+        return;
+    }
+    thresholds.dedup();
+    for threshold in thresholds {
+        // the remaining colors only add to the total
+        if best
+            .as_ref()
+            .is_some_and(|(b, _)| total.saturating_add(threshold) >= *b)
+        {
+            break;
+        }
+        let remaining = candidates
+            .iter()
+            .copied()
+            .filter(|g| minimums[*g][color] <= threshold)
+            .collect::<Vec<usize>>();
+        if remaining.len() >= k {
+            chosen.push(threshold);
+            search_bag(minimums, k, &remaining, chosen, best);
+            chosen.pop();
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ColorStats {
+    color: String,
+    // games and draws the color shows up in
+    games: usize,
+    draws: usize,
+    total: u64,
+    most: u64,
+}
+
+fn color_stats(games: &[Game]) -> Vec<ColorStats> {
+    colors(games)
+        .into_iter()
+        .map(|color| {
+            let counts = |game: &Game| {
+                game.draws
+                    .iter()
+                    .filter_map(|d| d.cubes.get(color).copied())
+                    .collect::<Vec<u64>>()
+            };
+            let all = games.iter().map(counts).collect::<Vec<Vec<u64>>>();
+            ColorStats {
+                color: color.clone(),
+                games: all.iter().filter(|c| !c.is_empty()).count(),
+                draws: all.iter().map(|c| c.len()).sum(),
+                total: all
+                    .iter()
+                    .flatten()
+                    .fold(0u64, |sum, c| sum.saturating_add(*c)),
+                most: all.iter().flatten().copied().max().unwrap_or(0),
             }
-            i as i32 + 1
         })
-        .collect::<Vec<i32>>()
+        .collect()
+}
+
+fn solve_part_a(input: &Vec<String>, bag: &Cubes) -> Result<u64, InputError> {
+    possible_games(&parse_games(input)?, bag)
         .iter()
-        .sum()
+        .try_fold(0u64, |sum, game| sum.checked_add(game.id))
+        .ok_or_else(overflow)
 }
 
-fn solve_part_b(input: &Vec<String>) -> i32 {
-    input
+fn solve_part_b(input: &Vec<String>) -> Result<u64, InputError> {
+    let games = parse_games(input)?;
+    let colors = colors(&games);
+    games
         .iter()
-        .map(|line| {
-            let mut red: i32 = 0;
-            let mut green: i32 = 0;
-            let mut blue: i32 = 0;
-            let s = *line.split(":").collect::<Vec<&str>>().get(1).unwrap();
-            for draw in s.replace(";", ",").split(",") {
-                let seg = draw.split(" ").collect::<Vec<&str>>();
-                match *seg.get(2).unwrap() {
-                    "red" => red = red.max(seg.get(1).unwrap().parse::<i32>().unwrap()),
-                    "green" => green = green.max(seg.get(1).unwrap().parse::<i32>().unwrap()),
-                    "blue" => blue = blue.max(seg.get(1).unwrap().parse::<i32>().unwrap()),
-                    _ => (),
-                }
-            }
-            red * green * blue
+        .map(|game| power(&game.minimum_bag(), &colors))
+        .try_fold(0u64, |sum, power| sum.checked_add(power?))
+        .ok_or_else(overflow)
+}
+
+// What can be asked from the command line, next to the usual part A and B.
+#[derive(Debug, PartialEq)]
+enum Query {
+    // possible [red=12,green=13,...], the configured bag if none is given
+    Possible(Option<Cubes>),
+    Minimum,
+    // smallest <k>
+    Smallest(usize),
+    Stats,
+}
+
+impl Query {
+    fn parse(args: &[&str]) -> Result<Query, InputError> {
+        match args {
+            ["possible"] => Ok(Query::Possible(None)),
+            ["possible", bag] => Ok(Query::Possible(Some(parse_bag(bag)?))),
+            ["minimum"] => Ok(Query::Minimum),
+            ["smallest", k] => Ok(Query::Smallest(parse_number(k, 0).map_err(|_| {
+                InputError::new(format!("smallest expects a number of games, found `{}`", k))
+            })?)),
+            ["stats"] => Ok(Query::Stats),
+            _ => Err(InputError::new(
+                "queries: possible [color=count,...] | minimum | smallest <k> | stats",
+            )),
+        }
+    }
+}
+
+// `red=12,green=13,blue=14`
+fn parse_bag(text: &str) -> Result<Cubes, InputError> {
+    text.split(',')
+        .map(|pair| {
+            pair.split_once('=')
+                .and_then(|(color, count)| {
+                    Some((color.trim().to_string(), count.trim().parse::<u64>().ok()?))
+                })
+                .filter(|(color, _)| !color.is_empty())
+                .ok_or_else(|| InputError::new(format!("expected `color=count`, found `{}`", pair)))
         })
-        .collect::<Vec<i32>>()
+        .collect()
+}
+
+fn format_cubes(cubes: &Cubes) -> String {
+    cubes
         .iter()
-        .sum()
+        .map(|(color, count)| format!("{} {}", count, color))
+        .collect::<Vec<String>>()
+        .join(", ")
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: i32 = solve_part_a(&input);
-    let result_part_b: i32 = solve_part_b(&input);
+fn answer(games: &[Game], query: &Query, bag: &Cubes) -> Result<String, InputError> {
+    Ok(match query {
+        Query::Possible(custom) => {
+            let bag = custom.as_ref().unwrap_or(bag);
+            let possible = possible_games(games, bag);
+            let ids = possible
+                .iter()
+                .map(|g| g.id.to_string())
+                .collect::<Vec<String>>();
+            let sum = possible
+                .iter()
+                .try_fold(0u64, |sum, g| sum.checked_add(g.id))
+                .ok_or_else(overflow)?;
+            format!(
+                "Bag: {}\nPossible games ({}): {}\nSum of ids: {}\n",
+                format_cubes(bag),
+                possible.len(),
+                ids.join(", "),
+                sum
+            )
+        }
+        Query::Minimum => {
+            let colors = colors(games);
+            let mut text = String::new();
+            for game in games {
+                let bag = game.minimum_bag();
+                let power = power(&bag, &colors).ok_or_else(overflow)?;
+                text += &format!(
+                    "Game {}: {} (power {})\n",
+                    game.id,
+                    format_cubes(&bag),
+                    power
+                );
+            }
+            text
+        }
+        Query::Smallest(k) => match smallest_bag(games, *k) {
+            Some(bag) => format!(
+                "Smallest bag for at least {} games: {} ({} cubes)\n",
+                k,
+                format_cubes(&bag),
+                bag.values().fold(0u64, |sum, c| sum.saturating_add(*c))
+            ),
+            None => format!("There are only {} games, not {}\n", games.len(), k),
+        },
+        Query::Stats => {
+            let mut text = String::from("color\tgames\tdraws\ttotal\tmost\n");
+            for s in color_stats(games) {
+                text += &format!(
+                    "{}\t{}\t{}\t{}\t{}\n",
+                    s.color, s.games, s.draws, s.total, s.most
+                );
+            }
+            text
+        }
+    })
+}
+
+// Arguments that are not `--config`/`--set` or their values.
+fn positional(args: &[String]) -> Vec<&str> {
+    let mut result: Vec<&str> = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--config" | "--set" => i += 1,
+            arg => result.push(arg),
+        }
+        i += 1;
+    }
+    result
+}
+
+// `cargo run -- <query>` answers one query about the games instead, see Query.
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = Config::from_args("./config.toml", args.clone().into_iter())?;
+    let mut bag: Cubes = config.section("bag")?.into_iter().collect();
+    if bag.is_empty() {
+        bag = Cubes::from([
+            ("red".to_string(), 12),
+            ("green".to_string(), 13),
+            ("blue".to_string(), 14),
+        ]);
+    }
+    let query = positional(&args);
+    if !query.is_empty() {
+        print!(
+            "{}",
+            answer(&parse_games(&input)?, &Query::parse(&query)?, &bag)?
+        );
+        return Ok(());
+    }
+    let result_part_a: u64 = solve_part_a(&input, &bag)?;
+    let result_part_b: u64 = solve_part_b(&input)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
+    fn bag(red: u64, green: u64, blue: u64) -> Cubes {
+        Cubes::from([
+            ("red".to_string(), red),
+            ("green".to_string(), green),
+            ("blue".to_string(), blue),
+        ])
+    }
+
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(8, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(8), solve_part_a(&example, &bag(12, 13, 14)));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(2286, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(2286), solve_part_b(&example));
+    }
+
+    #[test]
+    fn check_game_model() {
+        let game = parse_game(0, "Game 42: 3 blue, 4 red; 1 red, 2 teal; 6 blue").unwrap();
+        assert_eq!(42, game.id);
+        assert_eq!(3, game.draws.len());
+        assert_eq!(Some(&2), game.draws[1].cubes.get("teal"));
+        assert_eq!(
+            vec![("blue", 6), ("red", 4), ("teal", 2)],
+            game.minimum_bag()
+                .iter()
+                .map(|(c, n)| (c.as_str(), *n))
+                .collect::<Vec<(&str, u64)>>()
+        );
+        // no teal cubes in the standard bag
+        assert!(!game.is_possible(&bag(12, 13, 14)));
+        assert!(parse_game(0, "Game 1: 1 red, 2 red").is_err());
+        assert!(parse_game(0, "Gmae 1: 1 red").is_err());
+    }
+
+    #[test]
+    fn check_queries() {
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        let games = parse_games(&example).unwrap();
+        let ids = |bag: &Cubes| {
+            possible_games(&games, bag)
+                .iter()
+                .map(|g| g.id)
+                .collect::<Vec<u64>>()
+        };
+        assert_eq!(vec![1, 2, 5], ids(&bag(12, 13, 14)));
+        assert_eq!(vec![2], ids(&parse_bag("red=1,green=3,blue=4").unwrap()));
+        // games 1, 2 and 5 together need 6 red, 3 green and 6 blue
+        assert_eq!(Some(bag(6, 3, 6)), smallest_bag(&games, 3));
+        assert_eq!(Some(bag(20, 13, 15)), smallest_bag(&games, 5));
+        assert_eq!(None, smallest_bag(&games, 6));
+        let blue = &color_stats(&games)[0];
+        assert_eq!(
+            ("blue", 5, 11, 50, 15),
+            (
+                blue.color.as_str(),
+                blue.games,
+                blue.draws,
+                blue.total,
+                blue.most
+            )
+        );
+        assert_eq!(Ok(Query::Smallest(3)), Query::parse(&["smallest", "3"]));
+        assert!(Query::parse(&["largest"]).is_err());
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(
+            &["Game 1: 3 blue, 4 red; 1 red, 2 green\nGame 2: 20 red\n"],
+            allowed,
+            |input| {
+                let _ = solve_part_a(input, &bag(12, 13, 14));
+                let _ = solve_part_b(input);
+            },
+        );
     }
 }
@@ -1,137 +1,321 @@
-use std::{collections::HashMap, fs::read_to_string};
+use common::config::Config;
+use common::error::{overflow, parse_number, InputError};
+use common::input::read_input;
+use std::{collections::HashMap, ops::Range};
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
-    }
-    result
+fn allowed(c: char) -> bool {
+    c.is_ascii_graphic()
 }
 
-fn add_padding(input: &Vec<String>) -> Vec<Vec<String>> {
-    let mut local_input: Vec<Vec<String>> = input
-        .iter()
-        .map(|i| i.chars().map(|c| c.to_string()).collect::<Vec<String>>())
-        .collect::<Vec<Vec<String>>>();
-    let width = input.get(0).unwrap().len();
-    let width_padding: Vec<String> = (0..width).map(|_| ".".to_string()).collect::<Vec<String>>();
-    local_input.push(width_padding.clone());
-    local_input.insert(0, width_padding);
-    for i in local_input.iter_mut() {
-        i.insert(0, ".".to_string());
-        i.push(".".to_string());
-    }
-    local_input
-}
-
-fn solve_part_a(input: &Vec<String>) -> i32 {
-    let mut answer: i32 = 0;
-    let input = add_padding(input);
-    let mut numbers: Vec<String> = Vec::new();
-    let mut valid: bool = false;
-    let pos: Vec<(i32, i32)> = vec![
-        (1, 0),
-        (0, 1),
-        (1, 1),
-        (-1, 0),
-        (0, -1),
-        (-1, -1),
-        (1, -1),
-        (-1, 1),
-    ];
-    for i in 0..input.len() {
-        for j in 0..input.get(0).unwrap().len() {
-            let current = &input[i][j];
-            if current.chars().next().unwrap().is_ascii_digit() {
-                numbers.push(current.clone());
-                for x in &pos {
-                    let f = &input[(i as i32 + x.0) as usize][(j as i32 + x.1) as usize];
-                    if !f.chars().next().unwrap().is_ascii_digit() && f != "." {
-                        valid = true;
-                    }
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PartNumber {
+    value: u64,
+    row: usize,
+    // columns the digits occupy
+    span: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Symbol {
+    symbol: char,
+    row: usize,
+    column: usize,
+}
+
+// Numbers and symbols of the schematic, with which touch which. A number
+// touches every symbol in the ring of cells around its digits, diagonals
+// included, so it can touch several symbols and a symbol several numbers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Schematic {
+    numbers: Vec<PartNumber>,
+    symbols: Vec<Symbol>,
+    // by number index, the symbols it touches
+    symbols_of: Vec<Vec<usize>>,
+    // by symbol index, the numbers touching it
+    numbers_of: Vec<Vec<usize>>,
+}
+
+impl Schematic {
+    fn parse(input: &[String]) -> Result<Schematic, InputError> {
+        let width = match input.first() {
+            Some(line) => line.len(),
+            None => return Err(InputError::new("empty schematic")),
+        };
+        let mut numbers: Vec<PartNumber> = Vec::new();
+        let mut symbols: Vec<Symbol> = Vec::new();
+        for (row, line) in input.iter().enumerate() {
+            if !line.is_ascii() || line.len() != width {
+                return Err(InputError::at(
+                    row,
+                    format!("expected {} ascii characters per row", width),
+                ));
+            }
+            let bytes = line.as_bytes();
+            let mut column = 0;
+            while column < width {
+                let start = column;
+                while column < width && bytes[column].is_ascii_digit() {
+                    column += 1;
+                }
+                if column > start {
+                    numbers.push(PartNumber {
+                        value: parse_number(&line[start..column], row)?,
+                        row,
+                        span: start..column,
+                    });
+                    continue;
                 }
-            } else {
-                if valid {
-                    answer += numbers.join("").parse::<i32>().unwrap();
+                if bytes[column] != b'.' {
+                    symbols.push(Symbol {
+                        symbol: bytes[column] as char,
+                        row,
+                        column,
+                    });
                 }
-                valid = false;
-                numbers.clear();
+                column += 1;
             }
         }
-    }
-    answer
-}
-
-fn solve_part_b(input: &Vec<String>) -> i32 {
-    let input = add_padding(input);
-    let mut numbers: Vec<String> = Vec::new();
-    let mut valid = false;
-    let mut gear = (0, 0);
-    let mut valid_gear: HashMap<(i32, i32), Vec<i32>> = HashMap::new();
-    let pos: Vec<(i32, i32)> = vec![
-        (1, 0),
-        (0, 1),
-        (1, 1),
-        (-1, 0),
-        (0, -1),
-        (-1, -1),
-        (1, -1),
-        (-1, 1),
-    ];
-    for i in 0..input.len() {
-        for j in 0..input.get(0).unwrap().len() {
-            let current = &input[i][j];
-            if current.chars().next().unwrap().is_ascii_digit() {
-                numbers.push(current.clone());
-                for x in &pos {
-                    let f = &input[(i as i32 + x.0) as usize][(j as i32 + x.1) as usize];
-                    if f == "*" {
-                        valid = true;
-                        gear = (i as i32 + x.0, j as i32 + x.1);
+
+        let at = symbols
+            .iter()
+            .enumerate()
+            .map(|(i, s)| ((s.row, s.column), i))
+            .collect::<HashMap<(usize, usize), usize>>();
+        let mut symbols_of: Vec<Vec<usize>> = vec![Vec::new(); numbers.len()];
+        let mut numbers_of: Vec<Vec<usize>> = vec![Vec::new(); symbols.len()];
+        for (n, number) in numbers.iter().enumerate() {
+            for row in number.row.saturating_sub(1)..=number.row + 1 {
+                for column in number.span.start.saturating_sub(1)..=number.span.end {
+                    if let Some(s) = at.get(&(row, column)) {
+                        symbols_of[n].push(*s);
+                        numbers_of[*s].push(n);
                     }
                 }
-            } else {
-                if valid {
-                    let num = numbers.join("").parse::<i32>().unwrap();
-                    valid_gear
-                        .entry(gear)
-                        .and_modify(|v| v.push(num))
-                        .or_insert(vec![num]);
-                }
-                valid = false;
-                numbers.clear();
             }
         }
+        Ok(Schematic {
+            numbers,
+            symbols,
+            symbols_of,
+            numbers_of,
+        })
+    }
+
+    // Numbers next to at least one symbol.
+    fn part_numbers(&self) -> impl Iterator<Item = &PartNumber> {
+        self.numbers
+            .iter()
+            .zip(&self.symbols_of)
+            .filter(|(_, symbols)| !symbols.is_empty())
+            .map(|(number, _)| number)
+    }
+
+    fn numbers_touching(&self, symbol: usize) -> impl Iterator<Item = &PartNumber> {
+        self.numbers_of[symbol].iter().map(|n| &self.numbers[*n])
+    }
+}
+
+fn solve_part_a(input: &Vec<String>) -> Result<u64, InputError> {
+    Schematic::parse(input)?
+        .part_numbers()
+        .try_fold(0u64, |sum, number| sum.checked_add(number.value))
+        .ok_or_else(overflow)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Neighbors {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Aggregate {
+    Sum,
+    Product,
+    Max,
+}
+
+// Which symbols count as gears and how their numbers combine. The puzzle's
+// rule is a `*` with exactly two numbers, multiplied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GearRule {
+    // an empty set means any symbol
+    symbols: String,
+    neighbors: Neighbors,
+    aggregate: Aggregate,
+}
+
+impl Default for GearRule {
+    fn default() -> GearRule {
+        GearRule {
+            symbols: "*".to_string(),
+            neighbors: Neighbors::Exactly(2),
+            aggregate: Aggregate::Product,
+        }
+    }
+}
+
+impl GearRule {
+    // `neighbors` is `N` or `=N` for exactly N, `>=N` for at least N, and
+    // `aggregate` one of sum, product or max.
+    fn parse(symbols: &str, neighbors: &str, aggregate: &str) -> Result<GearRule, InputError> {
+        let count = |n: &str| {
+            n.trim().parse::<usize>().map_err(|_| {
+                InputError::new(format!(
+                    "expected `N`, `=N` or `>=N` neighbors, found `{}`",
+                    neighbors
+                ))
+            })
+        };
+        let neighbors = match neighbors.trim().strip_prefix(">=") {
+            Some(n) => Neighbors::AtLeast(count(n)?),
+            None => Neighbors::Exactly(count(neighbors.trim().trim_start_matches('='))?),
+        };
+        let aggregate = match aggregate.trim() {
+            "sum" => Aggregate::Sum,
+            "product" => Aggregate::Product,
+            "max" => Aggregate::Max,
+            other => {
+                return Err(InputError::new(format!(
+                    "expected sum, product or max, found `{}`",
+                    other
+                )))
+            }
+        };
+        Ok(GearRule {
+            symbols: symbols.to_string(),
+            neighbors,
+            aggregate,
+        })
+    }
+
+    fn is_gear(&self, symbol: char, numbers: usize) -> bool {
+        (self.symbols.is_empty() || self.symbols.contains(symbol))
+            && match self.neighbors {
+                Neighbors::Exactly(n) => numbers == n,
+                Neighbors::AtLeast(n) => numbers >= n,
+            }
+    }
+
+    fn combine<'a>(&self, mut values: impl Iterator<Item = &'a u64>) -> Option<u64> {
+        match self.aggregate {
+            Aggregate::Sum => values.try_fold(0u64, |sum, v| sum.checked_add(*v)),
+            Aggregate::Product => values.try_fold(1u64, |product, v| product.checked_mul(*v)),
+            Aggregate::Max => Some(values.max().copied().unwrap_or(0)),
+        }
     }
-    valid_gear
+}
+
+// Every symbol the rule accepts as a gear with its combined value.
+fn gears(schematic: &Schematic, rule: &GearRule) -> Result<Vec<(Symbol, u64)>, InputError> {
+    schematic
+        .symbols
         .iter()
-        .filter(|&(_, v)| v.len() == 2)
-        .map(|(_, v)| v.iter().copied().reduce(|a, b| a * b).unwrap())
-        .collect::<Vec<i32>>()
+        .enumerate()
+        .filter(|(s, symbol)| rule.is_gear(symbol.symbol, schematic.numbers_of[*s].len()))
+        .map(|(s, symbol)| {
+            let value = rule
+                .combine(schematic.numbers_touching(s).map(|n| &n.value))
+                .ok_or_else(overflow)?;
+            Ok((*symbol, value))
+        })
+        .collect()
+}
+
+fn solve_part_b(input: &Vec<String>, rule: &GearRule) -> Result<u64, InputError> {
+    gears(&Schematic::parse(input)?, rule)?
         .iter()
-        .sum()
+        .try_fold(0u64, |sum, (_, value)| sum.checked_add(*value))
+        .ok_or_else(overflow)
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: i32 = solve_part_a(&input);
-    let result_part_b: i32 = solve_part_b(&input);
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let config = Config::from_args("./config.toml", std::env::args().skip(1))?;
+    let rule = GearRule::parse(
+        &config.get("gear.symbols", "*".to_string())?,
+        &config.get("gear.neighbors", "=2".to_string())?,
+        &config.get("gear.aggregate", "product".to_string())?,
+    )?;
+    let result_part_a: u64 = solve_part_a(&input)?;
+    let result_part_b: u64 = solve_part_b(&input, &rule)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(4361, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(4361), solve_part_a(&example));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(467835, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(467835), solve_part_b(&example, &GearRule::default()));
+    }
+
+    #[test]
+    fn check_schematic_adjacency() {
+        let input = vec!["*12*".to_string(), "3..4".to_string()];
+        let schematic = Schematic::parse(&input).unwrap();
+        assert_eq!(1..3, schematic.numbers[0].span);
+        // 12 touches both gears, each gear also touches the number below it
+        assert_eq!(vec![0, 1], schematic.symbols_of[0]);
+        assert_eq!(vec![0, 2], schematic.numbers_of[1]);
+        assert_eq!(
+            Ok(12 * 3 + 12 * 4),
+            solve_part_b(&input, &GearRule::default())
+        );
+        assert_eq!(
+            vec![12, 3, 4],
+            schematic
+                .part_numbers()
+                .map(|n| n.value)
+                .collect::<Vec<u64>>()
+        );
+    }
+
+    #[test]
+    fn check_gear_rules() {
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        let schematic = Schematic::parse(&example).unwrap();
+        let total = |rule: GearRule| {
+            gears(&schematic, &rule)
+                .unwrap()
+                .iter()
+                .map(|(_, v)| v)
+                .sum::<u64>()
+        };
+        // the `*` next to 617 alone
+        assert_eq!(617, total(GearRule::parse("*", "1", "sum").unwrap()));
+        // every symbol, however many numbers, its largest number
+        assert_eq!(
+            467 + 755 + 617 + 633 + 592 + 664,
+            total(GearRule::parse("", ">=1", "max").unwrap())
+        );
+        assert_eq!(
+            633 + 592 + 664,
+            total(GearRule::parse("#+$", ">=0", "product").unwrap())
+        );
+        assert!(GearRule::parse("*", "two", "sum").is_err());
+        assert!(GearRule::parse("*", "2", "mean").is_err());
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(&["12..5\n..*..\n.3.#7\n"], allowed, |input| {
+            let _ = solve_part_a(input);
+            let _ = solve_part_b(input, &GearRule::default());
+        });
     }
 }
@@ -1,123 +1,234 @@
-use std::{
-    collections::{HashMap, HashSet},
-    fs::read_to_string,
-};
-
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
+use common::config::Config;
+use common::error::{overflow, parse_number, InputError};
+use common::input::read_input;
+use std::collections::HashSet;
+
+fn allowed(c: char) -> bool {
+    c.is_ascii_alphanumeric() || " :|".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Card {
+    id: u64,
+    winning: Vec<u64>,
+    have: Vec<u64>,
+}
+
+impl Card {
+    // How many of the numbers we have are winning numbers.
+    fn matches(&self) -> usize {
+        let winning = self.winning.iter().collect::<HashSet<&u64>>();
+        self.have.iter().filter(|n| winning.contains(n)).count()
     }
-    result
 }
 
-fn parse_input(input: &String) -> (Vec<i32>, Vec<i32>) {
-    let a = input
-        .split(":")
-        .collect::<Vec<&str>>()
-        .get(1)
-        .unwrap()
-        .to_string();
-    let b = a.split(" |").collect::<Vec<&str>>();
-    let winnings: Vec<i32> = b
-        .get(0)
-        .unwrap()
-        .chars()
-        .collect::<Vec<char>>()
-        .chunks(3)
-        .map(|c| {
-            c.iter()
-                .collect::<String>()
-                .to_string()
-                .trim()
-                .parse::<i32>()
-                .unwrap()
-        })
-        .collect::<Vec<i32>>();
-    let numbers: Vec<i32> = b
-        .get(1)
-        .unwrap()
-        .chars()
-        .collect::<Vec<char>>()
-        .chunks(3)
-        .map(|c| {
-            c.iter()
-                .collect::<String>()
-                .to_string()
-                .trim()
-                .parse::<i32>()
-                .unwrap()
-        })
-        .collect::<Vec<i32>>();
-    (winnings, numbers)
+fn parse_numbers(index: usize, numbers: &str) -> Result<Vec<u64>, InputError> {
+    numbers
+        .split_whitespace()
+        .map(|n| parse_number::<u64>(n, index))
+        .collect()
+}
+
+// `Card 1: 41 48 83 | 83 86  6`, any spacing and any number width
+fn parse_card(index: usize, line: &str) -> Result<Card, InputError> {
+    let (name, numbers) = line
+        .split_once(':')
+        .ok_or_else(|| InputError::at(index, "missing `:` after the card id"))?;
+    let id = match name.split_whitespace().collect::<Vec<&str>>()[..] {
+        ["Card", id] => parse_number::<u64>(id, index)?,
+        _ => return Err(InputError::at(index, "expected `Card <id>:`")),
+    };
+    let (winning, have) = numbers
+        .split_once('|')
+        .ok_or_else(|| InputError::at(index, "missing `|` between the number lists"))?;
+    Ok(Card {
+        id,
+        winning: parse_numbers(index, winning)?,
+        have: parse_numbers(index, have)?,
+    })
 }
 
-fn solve_part_a(input: &Vec<String>) -> i32 {
+fn parse_cards(input: &[String]) -> Result<Vec<Card>, InputError> {
     input
         .iter()
-        .map(|i| {
-            let (winning, numbers) = parse_input(i);
-            let winning = winning.into_iter().collect::<HashSet<i32>>();
-            let matches: i32 = numbers
-                .iter()
-                .map(|n| if winning.contains(n) { 1 } else { 0 })
-                .collect::<Vec<i32>>()
-                .iter()
-                .sum();
-            if matches > 0 {
-                i32::pow(2, matches as u32 - 1)
-            } else {
-                0
-            }
-        })
-        .collect::<Vec<i32>>()
-        .iter()
-        .sum()
+        .enumerate()
+        .map(|(i, line)| parse_card(i, line))
+        .collect()
+}
+
+// Points a card is worth for its number of matches. No matches are always
+// worth nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Scoring {
+    // 1 for the first match, doubled for every further one
+    Doubling,
+    // one point per match
+    Linear,
+    // base^(matches - 1)
+    Power(u64),
+    // points for 1, 2, ... matches, the last entry for anything beyond
+    Table(Vec<u64>),
 }
 
-fn solve_part_b(input: &Vec<String>) -> i32 {
-    let mut scratchcards: HashMap<i32, i32> = (0..input.len())
-        .map(|k| (k as i32, 1))
-        .collect::<HashMap<i32, i32>>();
-    for (i, card) in input.iter().enumerate() {
-        let (winning, numbers) = parse_input(card);
-        let winning = winning.into_iter().collect::<HashSet<i32>>();
-        let matches: i32 = numbers
-            .iter()
-            .map(|n| if winning.contains(n) { 1 } else { 0 })
-            .collect::<Vec<i32>>()
-            .iter()
-            .sum();
-        if matches > 0 {
-            let num = *scratchcards.get(&(i as i32)).unwrap();
-            for j in 1..matches + 1 {
-                scratchcards.entry(i as i32 + j).and_modify(|v| *v += num);
+impl Scoring {
+    // `doubling`, `linear`, `power:<base>` or `table:<p1>,<p2>,...`
+    fn parse(text: &str) -> Result<Scoring, InputError> {
+        let invalid = || {
+            InputError::new(format!(
+                "expected doubling, linear, power:<base> or table:<points>,..., found `{}`",
+                text
+            ))
+        };
+        match text.trim().split_once(':') {
+            None if text.trim() == "doubling" => Ok(Scoring::Doubling),
+            None if text.trim() == "linear" => Ok(Scoring::Linear),
+            Some(("power", base)) => Ok(Scoring::Power(
+                base.trim().parse::<u64>().map_err(|_| invalid())?,
+            )),
+            Some(("table", points)) => {
+                let points = points
+                    .split(',')
+                    .map(|p| p.trim().parse::<u64>())
+                    .collect::<Result<Vec<u64>, _>>()
+                    .map_err(|_| invalid())?;
+                Ok(Scoring::Table(points))
             }
+            _ => Err(invalid()),
+        }
+    }
+
+    fn points(&self, matches: usize) -> Option<u64> {
+        if matches == 0 {
+            return Some(0);
+        }
+        let exponent = u32::try_from(matches - 1).ok()?;
+        match self {
+            Scoring::Doubling => 2u64.checked_pow(exponent),
+            Scoring::Linear => u64::try_from(matches).ok(),
+            Scoring::Power(base) => base.checked_pow(exponent),
+            Scoring::Table(points) => points.get(matches - 1).or(points.last()).copied(),
         }
     }
-    scratchcards.iter().map(|(_, v)| v).sum()
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: i32 = solve_part_a(&input);
-    let result_part_b: i32 = solve_part_b(&input);
+// Instances of every card held once the cascade has run: each instance of a
+// card with `m` matches wins one copy of each of the next `m` cards (by
+// position), never past the end of the table.
+fn cascade(cards: &[Card]) -> Result<Vec<u64>, InputError> {
+    let mut instances: Vec<u64> = vec![1; cards.len()];
+    for (i, card) in cards.iter().enumerate() {
+        let end = cards
+            .len()
+            .min(i.saturating_add(1).saturating_add(card.matches()));
+        for j in i + 1..end {
+            instances[j] = instances[j]
+                .checked_add(instances[i])
+                .ok_or_else(overflow)?;
+        }
+    }
+    Ok(instances)
+}
+
+fn solve_part_a(input: &Vec<String>, scoring: &Scoring) -> Result<u64, InputError> {
+    parse_cards(input)?
+        .iter()
+        .try_fold(0u64, |sum, card| {
+            sum.checked_add(scoring.points(card.matches())?)
+        })
+        .ok_or_else(overflow)
+}
+
+fn solve_part_b(input: &Vec<String>) -> Result<u64, InputError> {
+    cascade(&parse_cards(input)?)?
+        .iter()
+        .try_fold(0u64, |sum, n| sum.checked_add(*n))
+        .ok_or_else(overflow)
+}
+
+// `cargo run -- --cascade` lists what every card won instead of the totals.
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = Config::from_args("./config.toml", args.clone().into_iter())?;
+    if args.iter().any(|a| a == "--cascade") {
+        let cards = parse_cards(&input)?;
+        println!("card\tmatches\tcopies_won\tinstances");
+        for (card, instances) in cards.iter().zip(cascade(&cards)?) {
+            println!(
+                "{}\t{}\t{}\t{}",
+                card.id,
+                card.matches(),
+                instances - 1,
+                instances
+            );
+        }
+        return Ok(());
+    }
+    let scoring = Scoring::parse(&config.get("scoring", "doubling".to_string())?)?;
+    let result_part_a: u64 = solve_part_a(&input, &scoring)?;
+    let result_part_b: u64 = solve_part_b(&input)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(13, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(13), solve_part_a(&example, &Scoring::Doubling));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(30, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(30), solve_part_b(&example));
+    }
+
+    #[test]
+    fn check_cards_and_cascade() {
+        let card = parse_card(0, "Card  107:1   200 3|3\t1 1000").unwrap();
+        assert_eq!(
+            (107, vec![1, 200, 3], vec![3, 1, 1000]),
+            (card.id, card.winning.clone(), card.have.clone())
+        );
+        assert_eq!(2, card.matches());
+        assert!(parse_card(0, "Card 1: 1 2 3").is_err());
+
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(
+            Ok(vec![1, 2, 4, 8, 14, 1]),
+            cascade(&parse_cards(&example).unwrap())
+        );
+        // 4, 2, 2 and 1 matches
+        assert_eq!(Ok(9), solve_part_a(&example, &Scoring::Linear));
+        assert_eq!(
+            Ok(27 + 3 + 3 + 1),
+            solve_part_a(&example, &Scoring::parse("power:3").unwrap())
+        );
+        assert_eq!(
+            Ok(10 + 5 + 5 + 1),
+            solve_part_a(&example, &Scoring::parse("table:1,5,10").unwrap())
+        );
+        assert!(Scoring::parse("power:x").is_err());
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(
+            &["Card 1: 41 48  3 | 83 48  6 41\nCard 2:  1 21 | 69 21\n"],
+            allowed,
+            |input| {
+                let _ = solve_part_a(input, &Scoring::Doubling);
+                let _ = solve_part_b(input);
+            },
+        );
     }
 }
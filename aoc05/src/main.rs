@@ -1,46 +1,187 @@
-use std::fs::read_to_string;
+use common::config::Config;
+use common::error::{parse_number, InputError};
+use common::input::read_input;
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::Range,
+};
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
+fn allowed(c: char) -> bool {
+    c.is_ascii_alphanumeric() || " :-".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
+}
+
+fn parse_seeds(input: &Vec<String>) -> Result<Vec<i64>, InputError> {
+    let line = match input.first() {
+        Some(line) => line,
+        None => return Err(InputError::new("empty almanac")),
+    };
+    match line.split_once(": ") {
+        Some((_, seeds)) => seeds
+            .split(" ")
+            .map(|n| parse_number::<i64>(n, 0))
+            .collect::<Result<Vec<i64>, InputError>>(),
+        None => Err(InputError::at(0, "expected `seeds: ` followed by numbers")),
     }
-    result
 }
 
-fn parse_seeds(input: &Vec<String>) -> Vec<i64> {
-    input
-        .get(0)
-        .unwrap()
-        .clone()
-        .split(": ")
-        .nth(1)
-        .unwrap()
-        .split(" ")
-        .map(|n| n.parse::<i64>().unwrap())
-        .collect::<Vec<i64>>()
-}
-
-fn parse_maps(input: &Vec<String>) -> Vec<Vec<(i64, i64, i64)>> {
-    let mut maps: Vec<Vec<(i64, i64, i64)>> = Vec::new();
-    let mut current_map: Vec<(i64, i64, i64)> = Vec::new();
-    for line in &input[1..] {
-        if line.contains(" ") && !line.contains("map") {
-            let split = line
-                .split(" ")
-                .map(|n| n.parse::<i64>().unwrap())
-                .collect::<Vec<i64>>();
-            current_map.push((split[0], split[1], split[2]));
-        } else if current_map.len() > 0 {
-            maps.push(current_map.clone());
-            current_map.clear();
-        }
-    }
-    maps.push(current_map);
-    maps
-}
-
-fn transform(input: i64, map: &Vec<(i64, i64, i64)>) -> i64 {
+// (destination, source, length) rows of one map
+type Map = Vec<(i64, i64, i64)>;
+
+// One `<source>-to-<destination> map:` block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AlmanacMap {
+    source: String,
+    destination: String,
+    rows: Map,
+}
+
+fn parse_header(index: usize, line: &str) -> Result<(String, String), InputError> {
+    line.strip_suffix(" map:")
+        .and_then(|name| name.split_once("-to-"))
+        .filter(|(source, destination)| !source.is_empty() && !destination.is_empty())
+        .map(|(source, destination)| (source.to_string(), destination.to_string()))
+        .ok_or_else(|| InputError::at(index, "expected `<source>-to-<destination> map:`"))
+}
+
+fn parse_row(index: usize, line: &str) -> Result<(i64, i64, i64), InputError> {
+    let split = line
+        .split_whitespace()
+        .map(|n| parse_number::<i64>(n, index))
+        .collect::<Result<Vec<i64>, InputError>>()?;
+    if split.len() != 3 {
+        return Err(InputError::at(
+            index,
+            "expected destination, source and length",
+        ));
+    }
+    // both ends of the mapped ranges have to be representable
+    if split[2] < 0
+        || split[0].checked_add(split[2]).is_none()
+        || split[1].checked_add(split[2]).is_none()
+    {
+        return Err(InputError::at(index, "range out of bounds"));
+    }
+    Ok((split[0], split[1], split[2]))
+}
+
+// The maps in the order they appear, which need not be the order of the chain.
+fn parse_maps(input: &Vec<String>) -> Result<Vec<AlmanacMap>, InputError> {
+    let mut maps: Vec<AlmanacMap> = Vec::new();
+    for (i, line) in input.iter().enumerate().skip(1) {
+        if line.is_empty() {
+            continue;
+        }
+        if line.ends_with(':') {
+            let (source, destination) = parse_header(i, line)?;
+            if maps
+                .iter()
+                .any(|m| m.source == source && m.destination == destination)
+            {
+                return Err(InputError::at(
+                    i,
+                    format!("second {}-to-{} map", source, destination),
+                ));
+            }
+            maps.push(AlmanacMap {
+                source,
+                destination,
+                rows: Vec::new(),
+            });
+            continue;
+        }
+        match maps.last_mut() {
+            Some(map) => map.rows.push(parse_row(i, line)?),
+            None => return Err(InputError::at(i, "numbers before the first map header")),
+        }
+    }
+    Ok(maps)
+}
+
+// The maps leading from category `from` to `to`, found by name. Several maps
+// may leave the same category; the shortest chain wins.
+fn chain<'a>(
+    maps: &'a [AlmanacMap],
+    from: &str,
+    to: &str,
+) -> Result<Vec<&'a AlmanacMap>, InputError> {
+    // category -> map that first reached it
+    let mut reached: HashMap<&str, Option<&AlmanacMap>> = HashMap::from([(from, None)]);
+    let mut queue: VecDeque<&str> = VecDeque::from([from]);
+    while let Some(category) = queue.pop_front() {
+        if category == to {
+            let mut chain: Vec<&AlmanacMap> = Vec::new();
+            let mut current = to;
+            while let Some(Some(map)) = reached.get(current) {
+                chain.push(map);
+                current = &map.source;
+            }
+            chain.reverse();
+            return Ok(chain);
+        }
+        for map in maps.iter().filter(|m| m.source == category) {
+            if !reached.contains_key(map.destination.as_str()) {
+                reached.insert(&map.destination, Some(map));
+                queue.push_back(&map.destination);
+            }
+        }
+    }
+    Err(InputError::new(format!(
+        "no chain of maps from {} to {}",
+        from, to
+    )))
+}
+
+// One map doing what `first` followed by `second` does. The rows of `first`
+// are split where their images cross the rows of `second`, and the rows of
+// `second` apply as they are to numbers `first` leaves alone.
+fn compose(first: &Map, second: &Map) -> Map {
+    let mut rows: Map = Vec::new();
+    for &(dest, source, size) in first {
+        let image = dest..dest + size;
+        let mut moved: Vec<Range<i64>> = Vec::new();
+        for &(dest2, source2, size2) in second {
+            let start = image.start.max(source2);
+            let end = image.end.min(source2 + size2);
+            if start < end {
+                rows.push((
+                    dest2 + (start - source2),
+                    source + (start - dest),
+                    end - start,
+                ));
+                moved.push(start..end);
+            }
+        }
+        for rest in subtract(image.clone(), &moved) {
+            rows.push((
+                rest.start,
+                source + (rest.start - dest),
+                rest.end - rest.start,
+            ));
+        }
+    }
+    let sources = first
+        .iter()
+        .map(|&(_, source, size)| source..source + size)
+        .collect::<Vec<Range<i64>>>();
+    for &(dest2, source2, size2) in second {
+        for rest in subtract(source2..source2 + size2, &sources) {
+            rows.push((
+                dest2 + (rest.start - source2),
+                rest.start,
+                rest.end - rest.start,
+            ));
+        }
+    }
+    rows.retain(|(dest, source, _)| dest != source);
+    rows.sort_by_key(|&(_, source, _)| source);
+    rows
+}
+
+fn transform(input: i64, map: &Map) -> i64 {
     for &(dest, source, size) in map {
         if (source..(source + size)).contains(&input) {
             return dest + (input - source);
@@ -49,75 +190,252 @@ fn transform(input: i64, map: &Vec<(i64, i64, i64)>) -> i64 {
     input
 }
 
-fn transform_rev(input: i64, map: &Vec<(i64, i64, i64)>) -> i64 {
-    for &(dest, source, size) in map {
-        if (dest..(dest + size)).contains(&input) {
-            return source + (input - dest);
+// Sorted, non-overlapping and without empty ranges, touching ones merged.
+fn normalize(mut ranges: Vec<Range<i64>>) -> Vec<Range<i64>> {
+    ranges.retain(|r| !r.is_empty());
+    ranges.sort_by_key(|r| (r.start, r.end));
+    let mut merged: Vec<Range<i64>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
         }
     }
-    input
+    merged
+}
+
+// `range` minus every range in `holes`.
+fn subtract(range: Range<i64>, holes: &[Range<i64>]) -> Vec<Range<i64>> {
+    let mut rest = vec![range];
+    for hole in holes {
+        rest = rest
+            .into_iter()
+            .flat_map(|r| [r.start..r.end.min(hole.start), r.start.max(hole.end)..r.end])
+            .filter(|r| !r.is_empty())
+            .collect();
+    }
+    rest
 }
 
-fn solve_part_a(input: &Vec<String>) -> i64 {
-    let seeds: Vec<i64> = parse_seeds(input);
-    let maps: Vec<Vec<(i64, i64, i64)>> = parse_maps(input);
-    *seeds
+// The image of a set of seeds under one map: every range is split at the
+// source boundaries of the rows, the pieces inside a row are moved, the rest
+// keeps its numbers. Work grows with the number of boundaries, not with how
+// many numbers the ranges hold.
+fn map_ranges(ranges: &[Range<i64>], map: &Map) -> Vec<Range<i64>> {
+    let sources = map
         .iter()
-        .map(|seed: &i64| {
-            let mut trasnformed_seed = seed.clone();
-            maps.iter().for_each(|map| {
-                trasnformed_seed = transform(trasnformed_seed, map);
-            });
-            trasnformed_seed
-        })
-        .collect::<Vec<i64>>()
+        .map(|&(_, source, size)| source..source + size)
+        .collect::<Vec<Range<i64>>>();
+    let mut image: Vec<Range<i64>> = Vec::new();
+    for range in ranges {
+        for &(dest, source, size) in map {
+            let start = range.start.max(source);
+            let end = range.end.min(source + size);
+            if start < end {
+                image.push(dest + (start - source)..dest + (end - source));
+            }
+        }
+        image.extend(subtract(range.clone(), &sources));
+    }
+    normalize(image)
+}
+
+// The inverse: every number the map sends into `ranges`. A number can be
+// reached both through a row and by being left alone, so this is the union of
+// the rows' preimages and the part of `ranges` no row moves away.
+fn unmap_ranges(ranges: &[Range<i64>], map: &Map) -> Vec<Range<i64>> {
+    let sources = map
+        .iter()
+        .map(|&(_, source, size)| source..source + size)
+        .collect::<Vec<Range<i64>>>();
+    let mut preimage: Vec<Range<i64>> = Vec::new();
+    for range in ranges {
+        for &(dest, source, size) in map {
+            let start = range.start.max(dest);
+            let end = range.end.min(dest + size);
+            if start < end {
+                preimage.push(source + (start - dest)..source + (end - dest));
+            }
+        }
+        preimage.extend(subtract(range.clone(), &sources));
+    }
+    normalize(preimage)
+}
+
+// The maps from `from` to `to`, by default seed to location.
+fn resolve(input: &Vec<String>, from: &str, to: &str) -> Result<Vec<Map>, InputError> {
+    let maps = parse_maps(input)?;
+    Ok(chain(&maps, from, to)?
         .iter()
+        .map(|m| m.rows.clone())
+        .collect())
+}
+
+fn solve_part_a(input: &Vec<String>, from: &str, to: &str) -> Result<i64, InputError> {
+    let seeds: Vec<i64> = parse_seeds(input)?;
+    let maps: Vec<Map> = resolve(input, from, to)?;
+    seeds
+        .iter()
+        .map(|seed| maps.iter().fold(*seed, transform))
         .min()
-        .unwrap()
+        .ok_or_else(|| InputError::at(0, "no seeds"))
 }
 
-fn solve_part_b(input: &Vec<String>) -> i64 {
-    let seeds: Vec<i64> = parse_seeds(input);
-    let maps: Vec<Vec<(i64, i64, i64)>> = parse_maps(input);
-    let seed_ranges = seeds
+fn solve_part_b(input: &Vec<String>, from: &str, to: &str) -> Result<i64, InputError> {
+    let seeds: Vec<i64> = parse_seeds(input)?;
+    let maps: Vec<Map> = resolve(input, from, to)?;
+    if !seeds.len().is_multiple_of(2) {
+        return Err(InputError::at(
+            0,
+            "seeds must come in start and length pairs",
+        ));
+    }
+    if seeds
         .chunks(2)
-        .map(|s| (s[0], s[1]))
-        .collect::<Vec<(i64, i64)>>();
-    for i in 0..i64::MAX {
-        let mut trasnformed_seed = i;
-        maps.iter().rev().for_each(|map| {
-            trasnformed_seed = transform_rev(trasnformed_seed, map);
-        });
-        if seed_ranges
-            .iter()
-            .any(|s| ((s.0)..(s.0) + s.1).contains(&trasnformed_seed))
-        {
-            return i;
-        }
+        .any(|s| s[1] < 0 || s[0].checked_add(s[1]).is_none())
+    {
+        return Err(InputError::at(0, "seed range out of bounds"));
     }
-    0
+    let seed_ranges = normalize(seeds.chunks(2).map(|s| s[0]..s[0] + s[1]).collect());
+    maps.iter()
+        .fold(seed_ranges, |ranges, map| map_ranges(&ranges, map))
+        .first()
+        .map(|r| r.start)
+        .ok_or_else(|| InputError::at(0, "no seeds"))
+}
+
+// Every `from` whose `to` lies in `targets`, mapping backwards through the
+// chain.
+fn sources_for(
+    input: &Vec<String>,
+    from: &str,
+    to: &str,
+    targets: Range<i64>,
+) -> Result<Vec<Range<i64>>, InputError> {
+    Ok(resolve(input, from, to)?
+        .iter()
+        .rev()
+        .fold(vec![targets], |ranges, map| unmap_ranges(&ranges, map)))
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: i64 = solve_part_a(&input);
-    let result_part_b: i64 = solve_part_b(&input);
+// `cargo run -- --sources-for <start>..<end>` lists the ranges ending up in
+// those targets instead of solving, `--compose` prints the whole chain as one
+// map. The chain runs from `chain.from` to `chain.to` in config.toml.
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = Config::from_args("./config.toml", args.clone().into_iter())?;
+    let from = config.get("chain.from", "seed".to_string())?;
+    let to = config.get("chain.to", "location".to_string())?;
+    if let Some(i) = args.iter().position(|a| a == "--sources-for") {
+        let targets = args
+            .get(i + 1)
+            .and_then(|r| r.split_once(".."))
+            .and_then(|(start, end)| Some(start.parse::<i64>().ok()?..end.parse::<i64>().ok()?))
+            .ok_or_else(|| InputError::new("--sources-for expects a range like 0..100"))?;
+        for range in sources_for(&input, &from, &to, targets)? {
+            println!("{}..{}", range.start, range.end);
+        }
+        return Ok(());
+    }
+    if args.iter().any(|a| a == "--compose") {
+        let composed = resolve(&input, &from, &to)?
+            .iter()
+            .fold(Vec::new(), |composed, map| compose(&composed, map));
+        println!("{}-to-{} map:", from, to);
+        for (dest, source, size) in composed {
+            println!("{} {} {}", dest, source, size);
+        }
+        return Ok(());
+    }
+    let result_part_a: i64 = solve_part_a(&input, &from, &to)?;
+    let result_part_b: i64 = solve_part_b(&input, &from, &to)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(35, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(35), solve_part_a(&example, "seed", "location"));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(46, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(46), solve_part_b(&example, "seed", "location"));
+    }
+
+    #[test]
+    fn check_range_mapping() {
+        // 50 98 2 and 52 50 48 from the example's seed-to-soil map
+        let map: Map = vec![(50, 98, 2), (52, 50, 48)];
+        assert_eq!(
+            vec![0..10, 52..53, 98..100],
+            map_ranges(&[0..10, 50..51, 96..98], &map)
+        );
+        assert_eq!(vec![50..52, 55..58], map_ranges(&[98..100, 53..56], &map));
+        assert_eq!(
+            vec![98..100, 200..201],
+            unmap_ranges(&[50..52, 200..201], &map)
+        );
+        // 0 and 1 are reached from 98 and 99 but also by being left alone
+        assert_eq!(
+            vec![0..2, 5..6, 98..100],
+            unmap_ranges(&[0..2, 5..6], &vec![(0, 98, 2)])
+        );
+        assert_eq!(
+            vec![0..10, 100..110],
+            unmap_ranges(&[0..10, 100..110], &map)
+        );
+        for range in [0..5, 40..60, 90..120] {
+            let image = map_ranges(std::slice::from_ref(&range), &map);
+            let preimage = unmap_ranges(&image, &map);
+            assert!(subtract(range.clone(), &preimage).is_empty());
+        }
+    }
+
+    #[test]
+    fn check_named_chain_and_composition() {
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        let mut shuffled = example.clone();
+        // the seed-to-soil block moved to the end
+        let block = shuffled.drain(2..5).collect::<Vec<String>>();
+        shuffled.push(String::new());
+        shuffled.extend(block);
+        assert_eq!(Ok(35), solve_part_a(&shuffled, "seed", "location"));
+        assert_eq!(Ok(46), solve_part_b(&shuffled, "seed", "location"));
+
+        let maps = parse_maps(&example).unwrap();
+        let names = chain(&maps, "soil", "water")
+            .unwrap()
+            .iter()
+            .map(|m| m.destination.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(vec!["fertilizer", "water"], names);
+        assert!(chain(&maps, "location", "seed").is_err());
+        assert_eq!(0, chain(&maps, "seed", "seed").unwrap().len());
+
+        let all = resolve(&example, "seed", "humidity").unwrap();
+        let composed = all.iter().fold(Vec::new(), |c, map| compose(&c, map));
+        for seed in (-5..120).chain([79, 14, 55, 13]) {
+            assert_eq!(all.iter().fold(seed, transform), transform(seed, &composed));
+        }
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(
+            &["seeds: 79 14 55 13\n\nseed-to-soil map:\n50 98 2\n52 50 48\n\nsoil-to-location map:\n0 15 37\n"], allowed,
+            |input| {
+                let _ = solve_part_a(input, "seed", "location");
+                let _ = solve_part_b(input, "seed", "location");
+            },
+        );
     }
 }
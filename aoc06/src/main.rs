@@ -1,99 +1,273 @@
-use std::fs::read_to_string;
+use common::input::read_input;
+use common::{
+    config::Config,
+    error::{overflow, parse_number, InputError},
+    exact::isqrt,
+};
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
-    }
-    result
+fn allowed(c: char) -> bool {
+    c.is_ascii_alphanumeric() || " :".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
 }
 
-fn parse_line(line: &String) -> Vec<i64> {
+fn parse_line(index: usize, line: &String) -> Result<Vec<i64>, InputError> {
     line.split(" ")
         .filter(|s| *s != "")
-        .map(|s| s.parse::<i64>().unwrap())
-        .collect::<Vec<i64>>()
+        .map(|s| parse_number::<i64>(s, index))
+        .collect::<Result<Vec<i64>, InputError>>()
 }
 
-fn split_line(input: &Vec<String>) -> Vec<String> {
+fn split_line(input: &Vec<String>) -> Result<Vec<String>, InputError> {
+    if input.len() < 2 {
+        return Err(InputError::new("expected a time and a distance line"));
+    }
     input
         .iter()
-        .map(|l| {
-            (*l.split(":")
-                .map(|s| s.to_string())
-                .collect::<Vec<String>>()
-                .get(1)
-                .unwrap())
-            .clone()
+        .enumerate()
+        .map(|(i, l)| match l.split_once(":") {
+            Some((_, numbers)) => Ok(numbers.to_string()),
+            None => Err(InputError::at(i, "missing `:` after the label")),
         })
-        .collect::<Vec<String>>()
+        .collect::<Result<Vec<String>, InputError>>()
 }
 
-fn parse_input_a(input: &Vec<String>) -> Vec<(i64, i64)> {
-    let s = split_line(input);
+fn parse_input_a(input: &Vec<String>) -> Result<Vec<(i64, i64)>, InputError> {
+    let s = split_line(input)?;
 
-    parse_line(&s[0])
+    Ok(parse_line(0, &s[0])?
         .into_iter()
-        .zip(parse_line(&s[1]))
-        .collect::<Vec<(i64, i64)>>()
+        .zip(parse_line(1, &s[1])?)
+        .collect::<Vec<(i64, i64)>>())
 }
 
-fn parse_input_b(input: &Vec<String>) -> (i64, i64) {
-    let mut s = split_line(input);
+fn parse_input_b(input: &Vec<String>) -> Result<(i64, i64), InputError> {
+    let mut s = split_line(input)?;
     s[0].retain(|c| !c.is_whitespace());
     s[1].retain(|c| !c.is_whitespace());
 
-    (s[0].parse::<i64>().unwrap(), s[1].parse::<i64>().unwrap())
+    Ok((
+        parse_number::<i64>(&s[0], 0)?,
+        parse_number::<i64>(&s[1], 1)?,
+    ))
+}
+
+// How far a boat gets in a race of `time` ms after holding the button for
+// `hold` ms. Models with a closed form for the number of winning hold times
+// provide it, everything else is counted exactly by trying every hold time.
+trait BoatModel {
+    fn distance(&self, hold: i64, time: i64) -> Option<i128>;
+
+    fn closed_form(&self, _time: i64, _record: i64) -> Option<i64> {
+        None
+    }
+}
+
+// Speed grows by `acceleration` mm/ms for every ms the button is held. The
+// puzzle's boat has acceleration 1.
+struct Standard {
+    acceleration: i64,
+}
+
+impl BoatModel for Standard {
+    fn distance(&self, hold: i64, time: i64) -> Option<i128> {
+        (self.acceleration as i128)
+            .checked_mul(hold as i128)?
+            .checked_mul(time as i128 - hold as i128)
+    }
+
+    // a * h * (time - h) > record exactly when h * (time - h) > record / a,
+    // i.e. at least floor(record / a) + 1 as both sides are integers
+    fn closed_form(&self, time: i64, record: i64) -> Option<i64> {
+        let need = record.div_euclid(self.acceleration).checked_add(1)?;
+        let (low, high) = solve_quadratic(&time, &need);
+        Some((high - low + 1).max(0))
+    }
+}
+
+// Like Standard, but the boat never goes faster than `max_speed`.
+struct Capped {
+    acceleration: i64,
+    max_speed: i64,
+}
+
+impl BoatModel for Capped {
+    fn distance(&self, hold: i64, time: i64) -> Option<i128> {
+        let speed = (self.acceleration as i128)
+            .checked_mul(hold as i128)?
+            .min(self.max_speed as i128);
+        speed.checked_mul(time as i128 - hold as i128)
+    }
+
+    // Holds below `capped_from` never reach the cap and win like a Standard
+    // boat. From there on the boat covers max_speed * (time - h), which beats
+    // the record when time - h is at least floor(record / max_speed) + 1.
+    fn closed_form(&self, time: i64, record: i64) -> Option<i64> {
+        let capped_from = (self.max_speed - 1) / self.acceleration + 1;
+        let need = record.div_euclid(self.acceleration).checked_add(1)?;
+        let (low, high) = solve_quadratic(&time, &need);
+        let below = (high.min(capped_from - 1) - low + 1).max(0);
+        let last = time as i128 - (record.div_euclid(self.max_speed) as i128 + 1);
+        let at_cap = (last.min(time as i128) - capped_from as i128 + 1).max(0);
+        Some(below + at_cap as i64)
+    }
 }
 
+// `max_speed` 0 means no cap.
+fn boat_model(acceleration: i64, max_speed: i64) -> Result<Box<dyn BoatModel>, InputError> {
+    if acceleration < 1 || max_speed < 0 {
+        return Err(InputError::new(
+            "acceleration must be positive and max_speed not negative",
+        ));
+    }
+    Ok(match max_speed {
+        0 => Box::new(Standard { acceleration }),
+        _ => Box::new(Capped {
+            acceleration,
+            max_speed,
+        }),
+    })
+}
+
+// number of hold times that beat the record distance
+fn count_wins(model: &dyn BoatModel, time: i64, distance: i64) -> Result<i64, InputError> {
+    if time < 0 {
+        return Err(InputError::at(0, "race time cannot be negative"));
+    }
+    if let Some(wins) = model.closed_form(time, distance) {
+        return Ok(wins);
+    }
+    let mut wins: i64 = 0;
+    for hold in 0..=time {
+        if model.distance(hold, time).ok_or_else(overflow)? > distance as i128 {
+            wins += 1;
+        }
+    }
+    Ok(wins)
+}
+
+// smallest and largest hold time h with h * (time - h) >= distance, or an empty
+// range (low > high) when no hold time reaches the distance
 fn solve_quadratic(time: &i64, distance: &i64) -> (i64, i64) {
-    let time = *time as f64;
-    let distance = *distance as f64;
-    (
-        (0.5 * (time - f64::sqrt(f64::powi(time, 2) - 4.0 * distance))).ceil() as i64,
-        (0.5 * (time + f64::sqrt(f64::powi(time, 2) - 4.0 * distance))).floor() as i64,
-    )
+    let time = *time as i128;
+    let distance = *distance as i128;
+    let discriminant = time * time - 4 * distance;
+    if discriminant < 0 {
+        return (1, 0);
+    }
+    // the integer root can be one off the real one, walk to the exact boundary
+    let mut low = ((time - isqrt(discriminant)) / 2).max(0);
+    while low * (time - low) < distance {
+        low += 1;
+    }
+    while low > 0 && (low - 1) * (time - low + 1) >= distance {
+        low -= 1;
+    }
+    (low as i64, (time - low) as i64)
 }
 
-fn solve_part_a(input: &Vec<String>) -> i64 {
-    let races = parse_input_a(input);
+fn solve_part_a(input: &Vec<String>, model: &dyn BoatModel) -> Result<i64, InputError> {
+    let races = parse_input_a(input)?;
     races
         .iter()
-        .map(|(time, distance)| {
-            let (floor, ceil) = solve_quadratic(time, &(distance + 1));
-            ceil - floor + 1
-        })
-        .collect::<Vec<i64>>()
+        .map(|(time, distance)| count_wins(model, *time, *distance))
+        .collect::<Result<Vec<i64>, InputError>>()?
         .iter()
-        .product::<i64>()
+        .try_fold(1i64, |acc, wins| acc.checked_mul(*wins))
+        .ok_or_else(overflow)
 }
 
-fn solve_part_b(input: &Vec<String>) -> i64 {
-    let (time, distance) = parse_input_b(input);
-    let (floor, ceil) = solve_quadratic(&time, &(distance + 1));
-    ceil - floor + 1
+fn solve_part_b(input: &Vec<String>, model: &dyn BoatModel) -> Result<i64, InputError> {
+    let (time, distance) = parse_input_b(input)?;
+    count_wins(model, time, distance)
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: i64 = solve_part_a(&input);
-    let result_part_b: i64 = solve_part_b(&input);
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let config = Config::from_args("./config.toml", std::env::args().skip(1))?;
+    let model = boat_model(
+        config.get("boat.acceleration", 1)?,
+        config.get("boat.max_speed", 0)?,
+    )?;
+    let result_part_a: i64 = solve_part_a(&input, model.as_ref())?;
+    let result_part_b: i64 = solve_part_b(&input, model.as_ref())?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(288, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(
+            Ok(288),
+            solve_part_a(&example, &Standard { acceleration: 1 })
+        );
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(71503, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(
+            Ok(71503),
+            solve_part_b(&example, &Standard { acceleration: 1 })
+        );
+    }
+
+    #[test]
+    fn check_large_race_is_exact() {
+        let (floor, ceil) = solve_quadratic(&6000000001, &9000000003000000000);
+        assert_eq!(2, ceil - floor + 1);
+    }
+
+    #[test]
+    fn check_boat_models() {
+        // the closed forms agree with trying every hold time
+        struct Searched(Box<dyn BoatModel>);
+        impl BoatModel for Searched {
+            fn distance(&self, hold: i64, time: i64) -> Option<i128> {
+                self.0.distance(hold, time)
+            }
+        }
+        for acceleration in 1..4 {
+            for max_speed in [0, 1, 2, 3, 5, 6, 12, 100] {
+                let model = boat_model(acceleration, max_speed).unwrap();
+                let searched = Searched(boat_model(acceleration, max_speed).unwrap());
+                for (time, record) in [(7, 9), (30, 200), (30, 201), (12, -1), (5, 1000), (0, 0)] {
+                    assert_eq!(
+                        count_wins(model.as_ref(), time, record),
+                        count_wins(&searched, time, record)
+                    );
+                }
+            }
+        }
+        // 7 ms race, record 9, speed capped at 3: hold 1 goes 1 * 6 = 6,
+        // hold 2 goes 2 * 5 = 10, hold 3 goes 3 * 4 = 12 and hold 4 only
+        // 3 * 3 = 9, so holds 2 and 3 win
+        let capped = boat_model(1, 3).unwrap();
+        assert_eq!(Ok(2), count_wins(capped.as_ref(), 7, 9));
+        assert_eq!(
+            Ok(999_999_999_999),
+            count_wins(capped.as_ref(), 1_000_000_000_000, 0)
+        );
+        assert!(boat_model(0, 0).is_err());
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(
+            &["Time:      7  15   30\nDistance:  9  40  200\n"],
+            allowed,
+            |input| {
+                let _ = solve_part_a(input, &Standard { acceleration: 1 });
+                let _ = solve_part_b(input, &Standard { acceleration: 1 });
+            },
+        );
     }
 }
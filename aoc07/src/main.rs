@@ -1,148 +1,448 @@
-use std::{cmp::Ordering, collections::HashMap, fs::read_to_string};
+use common::config::Config;
+use common::error::{overflow, parse_number, InputError};
+use common::input::read_input;
+use std::collections::HashMap;
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
+fn allowed(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == ' '
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
+}
+
+// How wildcards count towards the type of a hand. They always keep their own
+// place in the ranking for breaking ties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Substitution {
+    // join the largest group, which gives the strongest type possible
+    Best,
+    // become the highest ranked other card in the hand
+    Highest,
+    // stay single cards that never match anything
+    Singles,
+}
+
+impl Substitution {
+    // the name `Rules::parse` reads it back from
+    fn name(&self) -> &'static str {
+        match self {
+            Substitution::Best => "best",
+            Substitution::Highest => "highest",
+            Substitution::Singles => "singles",
+        }
     }
-    result
 }
 
-enum Part {
-    A,
-    B,
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rules {
+    // weakest card first
+    ranking: Vec<char>,
+    wildcards: Vec<char>,
+    substitution: Substitution,
+    // cards per hand, 0 for any size as long as all hands agree
+    size: usize,
 }
 
-fn level_a(cards: &String) -> u64 {
-    let mut counter: HashMap<String, u64> = HashMap::new();
-    for c in cards.chars() {
-        *counter.entry(c.to_string()).or_default() += 1;
+impl Rules {
+    fn part_a() -> Rules {
+        Rules::parse("23456789TJQKA", "", "best", 5).unwrap()
     }
-    let (_, max) = counter.iter().max_by_key(|(_, v)| *v).unwrap();
-    let max = max.clone();
-    match max {
-        1 => 1,
-        2 => 1 + counter.iter().filter(|(_, v)| **v == 2).count() as u64,
-        3 => 4 + counter.iter().any(|(_, v)| *v == 2) as u64,
-        4 => 6,
-        5 => 7,
-        _ => 0,
+
+    fn part_b() -> Rules {
+        Rules::parse("J23456789TQKA", "J", "best", 5).unwrap()
     }
-}
-fn level_b(cards: &String) -> u64 {
-    let mut counter: HashMap<String, u64> = HashMap::new();
-    for c in cards.chars() {
-        *counter.entry(c.to_string()).or_default() += 1;
+
+    fn parse(
+        ranking: &str,
+        wildcards: &str,
+        substitution: &str,
+        size: usize,
+    ) -> Result<Rules, InputError> {
+        let ranking: Vec<char> = ranking.chars().collect();
+        if ranking.is_empty()
+            || ranking
+                .iter()
+                .enumerate()
+                .any(|(i, c)| ranking[..i].contains(c))
+        {
+            return Err(InputError::new(
+                "the ranking must list every card exactly once",
+            ));
+        }
+        let wildcards: Vec<char> = wildcards.chars().collect();
+        if let Some(c) = wildcards.iter().find(|c| !ranking.contains(c)) {
+            return Err(InputError::new(format!(
+                "wildcard `{}` is not in the ranking",
+                c
+            )));
+        }
+        let substitution = match substitution.trim() {
+            "best" => Substitution::Best,
+            "highest" => Substitution::Highest,
+            "singles" => Substitution::Singles,
+            other => {
+                return Err(InputError::new(format!(
+                    "expected best, highest or singles, found `{}`",
+                    other
+                )))
+            }
+        };
+        Ok(Rules {
+            ranking,
+            wildcards,
+            substitution,
+            size,
+        })
     }
-    match counter
-        .iter()
-        .filter(|(k, _)| *k != "J")
-        .max_by_key(|(_, v)| *v)
-    {
-        Some((k, _)) => level_a(&cards.clone().replace("J", k)),
-        None => 7,
-    }
-}
-
-fn value(letter: String, part: &Part) -> u64 {
-    let values = match part {
-        Part::A => vec![
-            "2", "3", "4", "5", "6", "7", "8", "9", "T", "J", "Q", "K", "A",
-        ],
-        Part::B => vec![
-            "J", "2", "3", "4", "5", "6", "7", "8", "9", "T", "Q", "K", "A",
-        ],
-    };
-    values.iter().position(|&r| r == letter).unwrap() as u64
-}
-
-fn cmp(line: &(String, u64), other: &(String, u64), part: &Part) -> Ordering {
-    let (cards, _) = line;
-    let (other_cards, _) = other;
-    let card_level = match part {
-        Part::A => level_a(cards),
-        Part::B => level_b(cards),
-    };
-    let other_cards_level = match part {
-        Part::A => level_a(other_cards),
-        Part::B => level_b(other_cards),
-    };
-    if card_level > other_cards_level {
-        Ordering::Greater
-    } else if card_level < other_cards_level {
-        Ordering::Less
-    } else {
-        let mut other_cards_iter = other_cards.chars();
+
+    fn rank(&self, card: char) -> Option<usize> {
+        self.ranking.iter().position(|c| *c == card)
+    }
+
+    // Group sizes once the wildcards are placed, largest first. Comparing
+    // these lexicographically orders the types: [1, 1, 1, 1, 1] (high card)
+    // < [2, 1, 1, 1] < [2, 2, 1] < [3, 1, 1] < [3, 2] < [4, 1] < [5], and the
+    // same holds for any hand size.
+    fn shape(&self, cards: &str) -> Vec<usize> {
+        let mut counts: HashMap<char, usize> = HashMap::new();
+        let mut wild: usize = 0;
         for c in cards.chars() {
-            let next_other_char = other_cards_iter.next().unwrap().to_string();
-            let c_pos = value(c.to_string(), part);
-            let o_pos = value(next_other_char.to_string(), part);
-            if c_pos == o_pos {
-                continue;
-            } else if c_pos > o_pos {
-                return Ordering::Greater;
-            } else {
-                return Ordering::Less;
+            match self.wildcards.contains(&c) {
+                true => wild += 1,
+                false => *counts.entry(c).or_default() += 1,
             }
         }
-        Ordering::Equal
+        if self.substitution == Substitution::Highest {
+            if let Some(highest) = counts.keys().copied().max_by_key(|c| self.rank(*c)) {
+                *counts.entry(highest).or_default() += wild;
+                wild = 0;
+            }
+        }
+        let mut shape: Vec<usize> = counts.into_values().collect();
+        shape.sort_unstable_by(|a, b| b.cmp(a));
+        match self.substitution {
+            Substitution::Singles => shape.extend(std::iter::repeat_n(1, wild)),
+            _ if shape.is_empty() => shape.push(wild),
+            _ => shape[0] += wild,
+        }
+        shape.retain(|n| *n > 0);
+        shape
     }
-}
 
-fn parse_line(input: &Vec<String>) -> Vec<(String, u64)> {
-    input
-        .iter()
-        .map(|line| {
-            let a = line.split(" ").collect::<Vec<&str>>();
-            (a[0].to_string(), a[1].parse::<u64>().unwrap())
+    // The hand with every wildcard written as the card it stands in for, the
+    // largest group (highest ranked on a tie) for best. A hand of only
+    // wildcards becomes the strongest other card.
+    fn substituted(&self, cards: &str) -> String {
+        let natural = cards.chars().filter(|c| !self.wildcards.contains(c));
+        let target = match self.substitution {
+            Substitution::Singles => return cards.to_string(),
+            Substitution::Highest => natural.max_by_key(|c| self.rank(*c)),
+            Substitution::Best => {
+                natural.max_by_key(|c| (cards.matches(*c).count(), self.rank(*c)))
+            }
+        }
+        .or_else(|| {
+            self.ranking
+                .iter()
+                .rev()
+                .find(|c| !self.wildcards.contains(c))
+                .copied()
+        });
+        match target {
+            Some(target) => cards
+                .chars()
+                .map(|c| match self.wildcards.contains(&c) {
+                    true => target,
+                    false => c,
+                })
+                .collect(),
+            None => cards.to_string(),
+        }
+    }
+
+    fn hand(&self, cards: &str, bid: u64) -> Option<Hand> {
+        Some(Hand {
+            shape: self.shape(cards),
+            ranks: cards
+                .chars()
+                .map(|c| self.rank(c))
+                .collect::<Option<Vec<usize>>>()?,
+            cards: cards.to_string(),
+            bid,
         })
-        .collect::<Vec<(String, u64)>>()
+    }
 }
 
-fn solve_part_a(input: &Vec<String>) -> u64 {
-    let mut hands = parse_line(input);
-    hands.sort_by(|a, b| cmp(a, b, &Part::A));
-    hands
-        .iter()
-        .enumerate()
-        .map(|(i, hand)| (i as u64 + 1) * hand.1)
-        .collect::<Vec<u64>>()
-        .iter()
-        .sum()
+// Everything a comparison needs is worked out once, so sorting is a plain
+// comparison of the fields in order: type first, then card by card.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Hand {
+    shape: Vec<usize>,
+    ranks: Vec<usize>,
+    cards: String,
+    bid: u64,
 }
 
-fn solve_part_b(input: &Vec<String>) -> u64 {
-    let mut hands = parse_line(input);
-    hands.sort_by(|a, b| cmp(a, b, &Part::B));
+fn parse_hands(input: &[String], rules: &Rules) -> Result<Vec<Hand>, InputError> {
+    let mut hands: Vec<Hand> = Vec::new();
+    for (i, line) in input.iter().enumerate() {
+        let a = line.split(" ").collect::<Vec<&str>>();
+        if a.len() != 2 {
+            return Err(InputError::at(i, "expected a hand and a bid"));
+        }
+        let size = match hands.first() {
+            Some(first) => first.ranks.len(),
+            None => rules.size,
+        };
+        if size != 0 && a[0].chars().count() != size {
+            return Err(InputError::at(
+                i,
+                format!("expected {} cards, found `{}`", size, a[0]),
+            ));
+        }
+        let hand = rules
+            .hand(a[0], parse_number::<u64>(a[1], i)?)
+            .filter(|hand| !hand.ranks.is_empty())
+            .ok_or_else(|| InputError::at(i, format!("invalid hand `{}`", a[0])))?;
+        hands.push(hand);
+    }
+    Ok(hands)
+}
+
+fn total_winnings(hands: &[Hand]) -> Result<u64, InputError> {
     hands
         .iter()
         .enumerate()
-        .map(|(i, hand)| (i as u64 + 1) * hand.1)
-        .collect::<Vec<u64>>()
+        .try_fold(0u64, |acc, (i, hand)| {
+            acc.checked_add((i as u64 + 1).checked_mul(hand.bid)?)
+        })
+        .ok_or_else(overflow)
+}
+
+fn kind(shape: &[usize]) -> String {
+    match shape {
+        [5] => "five of a kind".to_string(),
+        [4, 1] => "four of a kind".to_string(),
+        [3, 2] => "full house".to_string(),
+        [3, 1, 1] => "three of a kind".to_string(),
+        [2, 2, 1] => "two pair".to_string(),
+        [2, 1, 1, 1] => "one pair".to_string(),
+        [1, 1, 1, 1, 1] => "high card".to_string(),
+        // other hand sizes, by their groups
+        _ => shape
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<String>>()
+            .join("+"),
+    }
+}
+
+// Why a hand ranks above the one just below it when both have the same type:
+// the first position where their cards differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TieBreak {
+    position: usize,
+    card: char,
+    other: char,
+    below: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Placement {
+    rank: usize,
+    cards: String,
+    form: String,
+    kind: String,
+    bid: u64,
+    winnings: u64,
+    tie_break: Option<TieBreak>,
+}
+
+fn tie_break(hand: &Hand, below: &Hand) -> Option<TieBreak> {
+    if hand.shape != below.shape {
+        return None;
+    }
+    let position = hand
+        .ranks
         .iter()
-        .sum()
+        .zip(&below.ranks)
+        .position(|(a, b)| a != b)?;
+    Some(TieBreak {
+        position: position + 1,
+        card: hand.cards.chars().nth(position)?,
+        other: below.cards.chars().nth(position)?,
+        below: below.cards.clone(),
+    })
+}
+
+fn ranking_report(input: &[String], rules: &Rules) -> Result<Vec<Placement>, InputError> {
+    let mut hands = parse_hands(input, rules)?;
+    hands.sort();
+    let mut report: Vec<Placement> = Vec::new();
+    for (i, hand) in hands.iter().enumerate() {
+        report.push(Placement {
+            rank: i + 1,
+            cards: hand.cards.clone(),
+            form: rules.substituted(&hand.cards),
+            kind: kind(&hand.shape),
+            bid: hand.bid,
+            winnings: (i as u64 + 1).checked_mul(hand.bid).ok_or_else(overflow)?,
+            tie_break: match i {
+                0 => None,
+                _ => tie_break(hand, &hands[i - 1]),
+            },
+        });
+    }
+    Ok(report)
+}
+
+// Tab separated, weakest hand first.
+fn report_table(report: &[Placement]) -> String {
+    let mut table = String::from("rank\thand\tform\ttype\tbid\twinnings\ttie_break\n");
+    for p in report {
+        let tie_break = match &p.tie_break {
+            Some(t) => format!(
+                "card {} ({} > {}) over {}",
+                t.position, t.card, t.other, t.below
+            ),
+            None => "-".to_string(),
+        };
+        table += &format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            p.rank, p.cards, p.form, p.kind, p.bid, p.winnings, tie_break
+        );
+    }
+    table
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input);
-    let result_part_b: u64 = solve_part_b(&input);
+fn solve(input: &[String], rules: &Rules) -> Result<u64, InputError> {
+    let mut hands = parse_hands(input, rules)?;
+    hands.sort();
+    total_winnings(&hands)
+}
+
+fn solve_part_a(input: &Vec<String>, rules: &Rules) -> Result<u64, InputError> {
+    solve(input, rules)
+}
+
+fn solve_part_b(input: &Vec<String>, rules: &Rules) -> Result<u64, InputError> {
+    solve(input, rules)
+}
+
+fn rules(config: &Config, part: &str, default: Rules) -> Result<Rules, InputError> {
+    let key = |name: &str| format!("{}.{}", part, name);
+    Rules::parse(
+        &config.get(&key("ranking"), default.ranking.iter().collect::<String>())?,
+        &config.get(
+            &key("wildcards"),
+            default.wildcards.iter().collect::<String>(),
+        )?,
+        &config.get(
+            &key("substitution"),
+            default.substitution.name().to_string(),
+        )?,
+        config.get(&key("size"), default.size)?,
+    )
+}
+
+// `cargo run -- --report [a|b]` prints every hand in ranking order instead of
+// the totals, under part B rules unless `a` is given.
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = Config::from_args("./config.toml", args.clone().into_iter())?;
+    let rules_a = rules(&config, "part_a", Rules::part_a())?;
+    let rules_b = rules(&config, "part_b", Rules::part_b())?;
+    if let Some(i) = args.iter().position(|a| a == "--report") {
+        let rules = match args.get(i + 1).map(|a| a.as_str()) {
+            Some("a") => &rules_a,
+            _ => &rules_b,
+        };
+        print!("{}", report_table(&ranking_report(&input, rules)?));
+        return Ok(());
+    }
+    let result_part_a: u64 = solve_part_a(&input, &rules_a)?;
+    let result_part_b: u64 = solve_part_b(&input, &rules_b)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(6440, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(6440), solve_part_a(&example, &Rules::part_a()));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(5905, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(5905), solve_part_b(&example, &Rules::part_b()));
+    }
+
+    #[test]
+    fn check_rule_variants() {
+        let shape = |rules: &Rules, cards: &str| rules.shape(cards);
+        let b = Rules::part_b();
+        assert_eq!(vec![4, 1], shape(&b, "KTJJT"));
+        assert_eq!(vec![5], shape(&b, "JJJJJ"));
+        let highest = Rules::parse("J23456789TQKA", "J", "highest", 5).unwrap();
+        assert_eq!(vec![3, 2], shape(&highest, "KTJJT"));
+        let singles = Rules::parse("J23456789TQKA", "J", "singles", 5).unwrap();
+        assert_eq!(vec![2, 1, 1, 1], shape(&singles, "KTJJT"));
+        // settings missing from the config keep the default's
+        let empty = Config::parse("").unwrap();
+        assert_eq!(
+            Ok(singles.clone()),
+            rules(&empty, "part_b", singles.clone())
+        );
+        // two wildcards, three card hands
+        let small = Rules::parse("*?abc", "*?", "best", 3).unwrap();
+        let input = vec![
+            "ab* 1".to_string(),
+            "cba 10".to_string(),
+            "?c* 100".to_string(),
+        ];
+        assert_eq!(Ok(312), solve(&input, &small));
+        assert!(parse_hands(&input, &Rules::part_a()).is_err());
+        assert!(Rules::parse("AKA", "", "best", 5).is_err());
+        assert!(Rules::parse("AKQ", "J", "best", 5).is_err());
+        assert!(Rules::parse("AKQ", "", "worst", 5).is_err());
+    }
+
+    #[test]
+    fn check_ranking_report() {
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        let report = ranking_report(&example, &Rules::part_b()).unwrap();
+        assert_eq!(
+            "rank\thand\tform\ttype\tbid\twinnings\ttie_break\n\
+             1\t32T3K\t32T3K\tone pair\t765\t765\t-\n\
+             2\tKK677\tKK677\ttwo pair\t28\t56\t-\n\
+             3\tT55J5\tT5555\tfour of a kind\t684\t2052\t-\n\
+             4\tQQQJA\tQQQQA\tfour of a kind\t483\t1932\tcard 1 (Q > T) over T55J5\n\
+             5\tKTJJT\tKTTTT\tfour of a kind\t220\t1100\tcard 1 (K > Q) over QQQJA\n",
+            report_table(&report)
+        );
+        assert_eq!(5905, report.iter().map(|p| p.winnings).sum::<u64>());
+        assert_eq!("AAAAA", Rules::part_b().substituted("JJJJJ"));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        let (rules_a, rules_b) = (Rules::part_a(), Rules::part_b());
+        fuzz_lines(
+            &["32T3K 765\nT55J5 684\nKTJJT 220\nJJJJJ 1\n"],
+            allowed,
+            move |input| {
+                let _ = solve_part_a(input, &rules_a);
+                let _ = solve_part_b(input, &rules_b);
+                let _ = ranking_report(input, &rules_b);
+            },
+        );
     }
 }
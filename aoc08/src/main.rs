@@ -1,112 +1,663 @@
-use std::{collections::HashMap, fs::read_to_string};
+use common::config::Config;
+use common::error::{overflow, InputError};
+use common::exact::{crt, gcd, Wide};
+use common::input::read_input;
+use std::collections::{HashMap, HashSet};
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
+fn allowed(c: char) -> bool {
+    c.is_ascii_alphanumeric() || " =(),".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
+}
+
+// Nodes are interned: every name gets an id in order of first appearance and
+// stepping is plain indexing into `next`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Network {
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    // id -> [left, right]
+    next: Vec<[usize; 2]>,
+}
+
+impl Network {
+    fn intern(&mut self, name: &str) -> usize {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        let id = self.names.len();
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        self.next.push([id, id]);
+        id
+    }
+
+    // ids of the nodes `selector` picks, in name order
+    fn select(&self, selector: &Selector) -> Result<Vec<usize>, InputError> {
+        if let Selector::Names(names) = selector {
+            if let Some(unknown) = names.iter().find(|n| !self.ids.contains_key(*n)) {
+                return Err(InputError::new(format!("unknown node {}", unknown)));
+            }
+        }
+        let mut ids: Vec<usize> = (0..self.names.len())
+            .filter(|id| selector.matches(&self.names[*id]))
+            .collect();
+        ids.sort_by_key(|id| &self.names[*id]);
+        Ok(ids)
     }
-    result
 }
 
-fn build_map(input: &[String]) -> HashMap<String, (String, String)> {
-    let mut map: HashMap<String, (String, String)> = HashMap::new();
-    input.iter().for_each(|line| {
-        map.insert(
-            line[0..3].to_string(),
-            (line[7..10].to_string(), line[12..15].to_string()),
-        );
-    });
-    map
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric())
 }
-fn solve_part_a(input: &Vec<String>) -> u64 {
-    let instructions = input.get(0).unwrap().clone().chars().collect::<Vec<char>>();
-    let map: HashMap<String, (String, String)> = build_map(&input[2..]);
-    let mut current_node = "AAA";
-    let mut counter: u64 = 0;
 
-    while current_node != "ZZZ" {
-        let (left, right) = map.get(current_node).unwrap();
-        let instruction = instructions[counter as usize % instructions.len()];
-        if instruction == 'L' {
-            current_node = left;
-        } else {
-            current_node = right;
+fn build_map(input: &[String]) -> Result<Network, InputError> {
+    let mut network = Network::default();
+    let mut defined: Vec<bool> = Vec::new();
+    let mut first_use: Vec<usize> = Vec::new();
+    for (i, line) in input.iter().enumerate() {
+        let (node, left, right) = line
+            .split_once(" = (")
+            .and_then(|(node, rest)| Some((node, rest.strip_suffix(')')?.split_once(", ")?)))
+            .map(|(node, (left, right))| (node, left, right))
+            .filter(|(node, left, right)| [node, left, right].iter().all(|n| valid_name(n)))
+            .ok_or_else(|| InputError::at(i + 2, "expected `AAA = (BBB, CCC)`"))?;
+        let ids = [node, left, right].map(|name| network.intern(name));
+        defined.resize(network.names.len(), false);
+        first_use.resize(network.names.len(), i + 2);
+        if defined[ids[0]] {
+            return Err(InputError::at(
+                i + 2,
+                format!("node {} defined twice", node),
+            ));
         }
-        counter += 1;
+        defined[ids[0]] = true;
+        network.next[ids[0]] = [ids[1], ids[2]];
+    }
+    match defined.iter().position(|d| !d) {
+        Some(id) => Err(InputError::at(
+            first_use[id],
+            format!("unknown node {}", network.names[id]),
+        )),
+        None => Ok(network),
+    }
+}
+
+// instructions as 0 for left and 1 for right
+fn parse_input(input: &Vec<String>) -> Result<(Vec<usize>, Network), InputError> {
+    let instructions = match input.first() {
+        Some(line) => line.chars().collect::<Vec<char>>(),
+        None => return Err(InputError::new("empty input")),
+    };
+    if instructions.is_empty() || instructions.iter().any(|c| *c != 'L' && *c != 'R') {
+        return Err(InputError::at(
+            0,
+            "instructions must be a non empty list of L and R",
+        ));
     }
+    Ok((
+        instructions.iter().map(|c| (*c == 'R') as usize).collect(),
+        build_map(input.get(2..).unwrap_or(&[]))?,
+    ))
+}
 
-    counter
+// Which nodes a ghost starts on or stops at. Written as a comma separated list
+// of names (`AAA` or `AAA,BBB`), a pattern with `*` at one end (`*Z` for the
+// names ending in Z, `1*` for those starting with 1) or `*` for every node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Selector {
+    Names(Vec<String>),
+    Prefix(String),
+    Suffix(String),
 }
 
-fn gcd(mut a: u64, mut b: u64) -> u64 {
-    while b != 0 {
-        let tmp = a;
-        a = b;
-        b = tmp % b;
+impl Selector {
+    fn parse(text: &str) -> Result<Selector, InputError> {
+        let text = text.trim();
+        let invalid = || {
+            InputError::new(format!(
+                "expected node names, `*X`, `X*` or `*`, found `{}`",
+                text
+            ))
+        };
+        if let Some(suffix) = text.strip_prefix('*') {
+            return match suffix.is_empty() || valid_name(suffix) {
+                true => Ok(Selector::Suffix(suffix.to_string())),
+                false => Err(invalid()),
+            };
+        }
+        if let Some(prefix) = text.strip_suffix('*') {
+            return match valid_name(prefix) {
+                true => Ok(Selector::Prefix(prefix.to_string())),
+                false => Err(invalid()),
+            };
+        }
+        let names: Vec<String> = text.split(',').map(|n| n.trim().to_string()).collect();
+        match names.iter().all(|n| valid_name(n)) {
+            true => Ok(Selector::Names(names)),
+            false => Err(invalid()),
+        }
     }
-    a
+
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            Selector::Names(names) => names.iter().any(|n| n == name),
+            Selector::Prefix(prefix) => name.starts_with(prefix.as_str()),
+            Selector::Suffix(suffix) => name.ends_with(suffix.as_str()),
+        }
+    }
+}
+
+// true at the id of every node `selector` picks
+fn selected(network: &Network, selector: &Selector) -> Result<Vec<bool>, InputError> {
+    let mut selected = vec![false; network.names.len()];
+    for id in network.select(selector)? {
+        selected[id] = true;
+    }
+    Ok(selected)
 }
 
-// lcm(a,b,c) -> lcm(a,lcm(b,c))
-fn lcm(numbers: &[u64]) -> u64 {
-    let a = numbers[0];
-    let mut b = numbers[1];
-    if numbers.len() > 2 {
-        b = lcm(&numbers[1..]);
+// Steps from `start` until an end node. Being back on a node at the same
+// point in the instructions means we are going around in circles.
+fn count_steps(
+    start: usize,
+    instructions: &[usize],
+    network: &Network,
+    is_end: &[bool],
+) -> Result<u64, InputError> {
+    let mut seen: HashSet<(usize, usize)> = HashSet::new();
+    let mut node = start;
+    let mut counter: u64 = 0;
+    while !is_end[node] {
+        let index = counter as usize % instructions.len();
+        if !seen.insert((node, index)) {
+            return Err(InputError::new(format!(
+                "no end node reachable from {}",
+                network.names[start]
+            )));
+        }
+        node = network.next[node][instructions[index]];
+        counter += 1;
     }
-    a * b / gcd(a, b)
+    Ok(counter)
+}
+
+fn solve_part_a(input: &Vec<String>, start: &Selector, end: &Selector) -> Result<u64, InputError> {
+    let (instructions, network) = parse_input(input)?;
+    let start = match network.select(start)?[..] {
+        [start] => start,
+        _ => return Err(InputError::new("part A needs exactly one start node")),
+    };
+    count_steps(start, &instructions, &network, &selected(&network, end)?)
 }
 
-fn solve_part_b(input: &Vec<String>) -> u64 {
-    let instructions = input.get(0).unwrap().clone().chars().collect::<Vec<char>>();
-    let map: HashMap<String, (String, String)> = build_map(&input[2..]);
-    let mut current_nodes: Vec<String> = Vec::new();
+// An end node reached `step` steps after the start, `index` into the
+// instructions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Arrival {
+    step: u64,
+    node: String,
+    index: usize,
+}
 
-    for (node, (_, _)) in &map {
-        if node.ends_with("A") {
-            current_nodes.push(node.clone());
+// A ghost's walk is decided by (node, instruction index), so after at most
+// nodes * instructions steps it runs into a state it has seen and repeats from
+// there forever: `pre_period` steps to get onto the loop, then `length` steps
+// around it. Arrivals before the loop happen once, those on it every `length`
+// steps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cycle {
+    start: String,
+    pre_period: u64,
+    length: u64,
+    once: Vec<Arrival>,
+    repeating: Vec<Arrival>,
+}
+
+impl Cycle {
+    fn arrives_at(&self, step: Wide) -> bool {
+        self.once.iter().any(|a| a.step as Wide == step)
+            || (step >= self.pre_period as Wide
+                && self
+                    .repeating
+                    .iter()
+                    .any(|a| (step - a.step as Wide).rem_euclid(self.length as Wide) == 0))
+    }
+}
+
+fn analyze_cycle(
+    start: usize,
+    instructions: &[usize],
+    network: &Network,
+    is_end: &[bool],
+) -> Cycle {
+    // first step at each (node, index), flattened
+    let mut seen: Vec<Option<u64>> = vec![None; network.names.len() * instructions.len()];
+    let mut arrivals: Vec<Arrival> = Vec::new();
+    let mut node = start;
+    let mut step: u64 = 0;
+    loop {
+        let index = step as usize % instructions.len();
+        let state = node * instructions.len() + index;
+        if let Some(first) = seen[state] {
+            let (once, repeating) = arrivals.into_iter().partition(|a| a.step < first);
+            return Cycle {
+                start: network.names[start].clone(),
+                pre_period: first,
+                length: step - first,
+                once,
+                repeating,
+            };
+        }
+        seen[state] = Some(step);
+        if is_end[node] {
+            arrivals.push(Arrival {
+                step,
+                node: network.names[node].clone(),
+                index,
+            });
         }
+        node = network.next[node][instructions[index]];
+        step += 1;
     }
-    let mut results: Vec<u64> = Vec::new();
-    for i in &current_nodes {
-        let mut counter: u64 = 0;
-        let mut node = i;
-        while !node.ends_with("Z") {
-            let (left, right) = map.get(node).unwrap();
-            let instruction = instructions[counter as usize % instructions.len()];
-            if instruction == 'L' {
-                node = left;
-            } else {
-                node = right;
+}
+
+// First step at which every ghost stands on an end node. Either it is one of
+// the arrivals before some loop, checked directly, or every ghost is on its
+// loop and the step solves one congruence per ghost, one per choice of
+// repeating arrival, combined with the generalized CRT.
+fn first_common_arrival(cycles: &[Cycle]) -> Result<Option<Wide>, InputError> {
+    let mut best: Option<Wide> = cycles
+        .iter()
+        .flat_map(|c| c.once.iter().map(|a| a.step as Wide))
+        .filter(|step| cycles.iter().all(|c| c.arrives_at(*step)))
+        .min();
+    let settled = cycles
+        .iter()
+        .map(|c| c.pre_period as Wide)
+        .max()
+        .unwrap_or(0);
+    // (residue, modulus) for every combination of arrivals chosen so far
+    let mut combined: Vec<(Wide, Wide)> = vec![(0, 1)];
+    for cycle in cycles {
+        let modulus = cycle.length as Wide;
+        let mut next: Vec<(Wide, Wide)> = Vec::new();
+        for congruence in &combined {
+            if (congruence.1 / gcd(congruence.1, modulus))
+                .checked_mul(modulus)
+                .is_none_or(|lcm| lcm > 1_000_000_000_000_000_000)
+            {
+                return Err(overflow());
             }
-            counter += 1;
+            next.extend(
+                cycle
+                    .repeating
+                    .iter()
+                    .filter_map(|a| crt(*congruence, (a.step as Wide, modulus))),
+            );
+        }
+        next.sort_unstable();
+        next.dedup();
+        combined = next;
+    }
+    for (residue, modulus) in combined {
+        // smallest step >= settled that is residue mod modulus
+        let step = settled + (residue - settled).rem_euclid(modulus);
+        best = Some(best.map_or(step, |b| b.min(step)));
+    }
+    Ok(best)
+}
+
+fn ghost_cycles(
+    instructions: &[usize],
+    network: &Network,
+    start: &Selector,
+    end: &Selector,
+) -> Result<Vec<Cycle>, InputError> {
+    let is_end = selected(network, end)?;
+    Ok(network
+        .select(start)?
+        .into_iter()
+        .map(|start| analyze_cycle(start, instructions, network, &is_end))
+        .collect())
+}
+
+fn solve_part_b(input: &Vec<String>, start: &Selector, end: &Selector) -> Result<u64, InputError> {
+    let (instructions, network) = parse_input(input)?;
+    let cycles = ghost_cycles(&instructions, &network, start, end)?;
+    if cycles.is_empty() {
+        return Err(InputError::new("no start node"));
+    }
+    if let Some(lost) = cycles
+        .iter()
+        .find(|c| c.once.is_empty() && c.repeating.is_empty())
+    {
+        return Err(InputError::new(format!(
+            "no end node reachable from {}",
+            lost.start
+        )));
+    }
+    match first_common_arrival(&cycles)? {
+        Some(step) => u64::try_from(step).map_err(|_| overflow()),
+        None => Err(InputError::new(
+            "the ghosts are never on end nodes at the same time",
+        )),
+    }
+}
+
+// Tab separated, one row per ghost, arrivals as step@node/index.
+fn cycle_table(cycles: &[Cycle]) -> String {
+    let arrivals = |list: &[Arrival]| {
+        list.iter()
+            .map(|a| format!("{}@{}/{}", a.step, a.node, a.index))
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+    let mut table = String::from("start\tpre_period\tlength\tonce\trepeating\n");
+    for c in cycles {
+        table += &format!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            c.start,
+            c.pre_period,
+            c.length,
+            arrivals(&c.once),
+            arrivals(&c.repeating)
+        );
+    }
+    table
+}
+
+// One step of a walk: standing on `node` at `step`, about to follow
+// instruction `index`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Visit {
+    step: u64,
+    node: usize,
+    index: usize,
+}
+
+// The walk from `start` up to and including the first state it has been in
+// before, so the last visit repeats the one at the returned step.
+fn trace(start: usize, instructions: &[usize], network: &Network) -> (Vec<Visit>, u64) {
+    let mut seen: HashMap<(usize, usize), u64> = HashMap::new();
+    let mut visits: Vec<Visit> = Vec::new();
+    let mut node = start;
+    let mut step: u64 = 0;
+    loop {
+        let index = step as usize % instructions.len();
+        visits.push(Visit { step, node, index });
+        if let Some(first) = seen.insert((node, index), step) {
+            return (visits, first);
         }
-        results.push(counter);
+        node = network.next[node][instructions[index]];
+        step += 1;
+    }
+}
+
+// Tab separated, one row per visit.
+fn trace_table(
+    visits: &[Visit],
+    repeats: u64,
+    instructions: &[usize],
+    network: &Network,
+    is_end: &[bool],
+) -> String {
+    let mut table = String::from("step\tnode\tindex\tmove\tend\tnote\n");
+    for (i, v) in visits.iter().enumerate() {
+        let note = match (i + 1 == visits.len(), v.step == repeats) {
+            (true, _) => format!("repeats step {}", repeats),
+            (false, true) => "loop starts".to_string(),
+            (false, false) => String::new(),
+        };
+        table += &format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\n",
+            v.step,
+            network.names[v.node],
+            v.index,
+            ["L", "R"][instructions[v.index]],
+            if is_end[v.node] { "yes" } else { "" },
+            note
+        );
     }
+    table
+}
 
-    lcm(results.as_slice())
+// Graphviz source for the network: start nodes green, end nodes red (both
+// for nodes that are both), edges labelled with the instruction taking them.
+//
+//     cargo run -- --dot > network.dot && dot -Tsvg network.dot -o network.svg
+fn network_dot(network: &Network, is_start: &[bool], is_end: &[bool]) -> String {
+    let mut dot = String::from("digraph network {\n    node [shape=circle];\n");
+    for (id, name) in network.names.iter().enumerate() {
+        let color = match (is_start[id], is_end[id]) {
+            (true, true) => "gold",
+            (true, false) => "palegreen",
+            (false, true) => "lightcoral",
+            (false, false) => continue,
+        };
+        dot += &format!("    \"{}\" [style=filled, fillcolor={}];\n", name, color);
+    }
+    for (id, [left, right]) in network.next.iter().enumerate() {
+        let from = &network.names[id];
+        if left == right {
+            dot += &format!(
+                "    \"{}\" -> \"{}\" [label=\"LR\"];\n",
+                from, network.names[*left]
+            );
+        } else {
+            dot += &format!(
+                "    \"{}\" -> \"{}\" [label=\"L\"];\n",
+                from, network.names[*left]
+            );
+            dot += &format!(
+                "    \"{}\" -> \"{}\" [label=\"R\"];\n",
+                from, network.names[*right]
+            );
+        }
+    }
+    dot + "}\n"
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input);
-    let result_part_b: u64 = solve_part_b(&input);
+// Instead of the totals, with part B's start and end nodes:
+// `cargo run -- --cycles` prints each ghost's cycle, `--trace <node>` the walk
+// from a node until it repeats and `--dot` the network as Graphviz source.
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = Config::from_args("./config.toml", args.clone().into_iter())?;
+    let selector =
+        |key: &str, default: &str| Selector::parse(&config.get(key, default.to_string())?);
+    let (start_a, end_a) = (
+        selector("part_a.start", "AAA")?,
+        selector("part_a.end", "ZZZ")?,
+    );
+    let (start_b, end_b) = (
+        selector("part_b.start", "*A")?,
+        selector("part_b.end", "*Z")?,
+    );
+    if args.iter().any(|a| a == "--cycles") {
+        let (instructions, network) = parse_input(&input)?;
+        let cycles = ghost_cycles(&instructions, &network, &start_b, &end_b)?;
+        print!("{}", cycle_table(&cycles));
+        return Ok(());
+    }
+    if let Some(i) = args.iter().position(|a| a == "--trace") {
+        let (instructions, network) = parse_input(&input)?;
+        let name = args.get(i + 1).map(|a| a.as_str()).unwrap_or("");
+        let start = *network
+            .ids
+            .get(name)
+            .ok_or_else(|| InputError::new(format!("unknown node `{}`", name)))?;
+        let (visits, repeats) = trace(start, &instructions, &network);
+        let is_end = selected(&network, &end_b)?;
+        print!(
+            "{}",
+            trace_table(&visits, repeats, &instructions, &network, &is_end)
+        );
+        return Ok(());
+    }
+    if args.iter().any(|a| a == "--dot") {
+        let (_, network) = parse_input(&input)?;
+        print!(
+            "{}",
+            network_dot(
+                &network,
+                &selected(&network, &start_b)?,
+                &selected(&network, &end_b)?
+            )
+        );
+        return Ok(());
+    }
+    let result_part_a: u64 = solve_part_a(&input, &start_a, &end_a)?;
+    let result_part_b: u64 = solve_part_b(&input, &start_b, &end_b)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
+    fn part_a() -> (Selector, Selector) {
+        (
+            Selector::parse("AAA").unwrap(),
+            Selector::parse("ZZZ").unwrap(),
+        )
+    }
+
+    fn part_b() -> (Selector, Selector) {
+        (
+            Selector::parse("*A").unwrap(),
+            Selector::parse("*Z").unwrap(),
+        )
+    }
+
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example-a.txt");
-        assert_eq!(2, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example-a.txt").unwrap();
+        let (start, end) = part_a();
+        assert_eq!(Ok(2), solve_part_a(&example, &start, &end));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example-b.txt");
-        assert_eq!(6, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example-b.txt").unwrap();
+        let (start, end) = part_b();
+        assert_eq!(Ok(6), solve_part_b(&example, &start, &end));
+    }
+
+    #[test]
+    fn check_cycle_analysis() {
+        let example: Vec<String> = read_lines("./inputs/example-b.txt").unwrap();
+        let (start, end) = part_b();
+        let (instructions, network) = parse_input(&example).unwrap();
+        let cycles = ghost_cycles(&instructions, &network, &start, &end).unwrap();
+        assert_eq!(
+            "start\tpre_period\tlength\tonce\trepeating\n\
+             11A\t1\t2\t\t2@11Z/0\n\
+             22A\t1\t6\t\t3@22Z/1 6@22Z/0\n",
+            cycle_table(&cycles)
+        );
+        // end nodes off the multiples of the first arrival: 2, 7, 12, ... and
+        // 3, 5, 7, ... first meet at 7, where the plain lcm of 2 and 3 says 6
+        let offset = [
+            "L",
+            "",
+            "11A = (11B, 11B)",
+            "11B = (11Z, 11Z)",
+            "11Z = (11C, 11C)",
+            "11C = (11D, 11D)",
+            "11D = (11E, 11E)",
+            "11E = (11F, 11F)",
+            "11F = (11Z, 11Z)",
+            "22A = (22B, 22B)",
+            "22B = (22C, 22C)",
+            "22C = (22Z, 22Z)",
+            "22Z = (22D, 22D)",
+            "22D = (22Z, 22Z)",
+        ]
+        .map(String::from)
+        .to_vec();
+        assert_eq!(Ok(7), solve_part_b(&offset, &start, &end));
+        // always one step out of phase
+        let apart = [
+            "L",
+            "",
+            "11A = (11Z, 11Z)",
+            "11Z = (11A, 11A)",
+            "22A = (22B, 22B)",
+            "22B = (22Z, 22Z)",
+            "22Z = (22B, 22B)",
+        ]
+        .map(String::from)
+        .to_vec();
+        assert!(solve_part_b(&apart, &start, &end).is_err());
+    }
+
+    #[test]
+    fn check_names_and_selectors() {
+        let input = [
+            "RL",
+            "",
+            "start = (a1, a1)",
+            "a1 = (goal, start)",
+            "goal = (goal, goal)",
+        ]
+        .map(String::from)
+        .to_vec();
+        let (instructions, network) = parse_input(&input).unwrap();
+        assert_eq!(vec![1, 0], instructions);
+        assert_eq!(vec!["start", "a1", "goal"], network.names);
+        assert_eq!([2, 0], network.next[1]);
+        let select = |text: &str| network.select(&Selector::parse(text).unwrap());
+        assert_eq!(Ok(vec![1, 0]), select("start,a1"));
+        assert_eq!(Ok(vec![1, 2, 0]), select("*"));
+        assert_eq!(Ok(vec![1]), select("a*"));
+        assert!(select("nowhere").is_err());
+        let [start, end] = ["start", "goal"].map(|s| Selector::parse(s).unwrap());
+        assert_eq!(Ok(2), solve_part_a(&input, &start, &end));
+        assert!(Selector::parse("a*b").is_err());
+        assert!(parse_input(&input[..4].to_vec()).is_err());
+    }
+
+    #[test]
+    fn check_trace_and_dot() {
+        let example: Vec<String> = read_lines("./inputs/example-b.txt").unwrap();
+        let (start, end) = part_b();
+        let (instructions, network) = parse_input(&example).unwrap();
+        let (visits, repeats) = trace(network.ids["11A"], &instructions, &network);
+        let is_end = selected(&network, &end).unwrap();
+        assert_eq!(
+            "step\tnode\tindex\tmove\tend\tnote\n\
+             0\t11A\t0\tL\t\t\n\
+             1\t11B\t1\tR\t\tloop starts\n\
+             2\t11Z\t0\tL\tyes\t\n\
+             3\t11B\t1\tR\t\trepeats step 1\n",
+            trace_table(&visits, repeats, &instructions, &network, &is_end)
+        );
+        let is_start = selected(&network, &start).unwrap();
+        let dot = network_dot(&network, &is_start, &is_end);
+        assert!(dot.starts_with("digraph network {\n"));
+        assert!(dot.contains("    \"11A\" [style=filled, fillcolor=palegreen];\n"));
+        assert!(dot.contains("    \"22Z\" [style=filled, fillcolor=lightcoral];\n"));
+        assert!(dot.contains("    \"11B\" -> \"XXX\" [label=\"L\"];\n"));
+        assert!(dot.contains("    \"22B\" -> \"22C\" [label=\"LR\"];\n"));
+        assert!(!dot.contains("\"XXX\" [style"));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(
+            &["LR\n\nAAA = (11Z, BBB)\nBBB = (AAA, ZZZ)\n11Z = (BBB, AAA)\nZZZ = (ZZZ, ZZZ)\n"],
+            allowed,
+            |input| {
+                let ((start_a, end_a), (start_b, end_b)) = (part_a(), part_b());
+                let _ = solve_part_a(input, &start_a, &end_a);
+                let _ = solve_part_b(input, &start_b, &end_b);
+            },
+        );
     }
 }
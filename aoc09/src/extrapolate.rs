@@ -0,0 +1,110 @@
+// Extrapolating a sequence by the polynomial through its values. The values
+// are taken at indices 0, 1, 2, ... and the polynomial is kept in Newton's
+// forward form
+//
+//     f(x) = d0 + d1 * C(x, 1) + d2 * C(x, 2) + ...
+//
+// where dk is the first entry of the k-th row of differences. C(x, k) is an
+// integer for every integer x, negative ones included, so the value at any
+// index comes out exact in integers without going through fractions.
+use common::exact::{gcd, Wide};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Extrapolation {
+    // first entry of each difference row, down to the last non-zero one
+    differences: Vec<Wide>,
+    // Degree of the polynomial through the values, 0 for a constant (or all
+    // zero) sequence.
+    pub(super) degree: usize,
+    // Whether the difference table reached a row of zeros. When it did not,
+    // the degree is only the most the values allow and every further value
+    // is a guess.
+    pub(super) determined: bool,
+}
+
+impl Extrapolation {
+    // None for an empty sequence or when a difference overflows.
+    pub(super) fn fit(values: &[i64]) -> Option<Extrapolation> {
+        let mut row: Vec<Wide> = values.iter().map(|v| *v as Wide).collect();
+        let mut differences: Vec<Wide> = Vec::new();
+        while row.iter().any(|v| *v != 0) {
+            differences.push(row[0]);
+            row = row
+                .windows(2)
+                .map(|w| w[1].checked_sub(w[0]))
+                .collect::<Option<Vec<Wide>>>()?;
+        }
+        if values.is_empty() {
+            return None;
+        }
+        Some(Extrapolation {
+            degree: differences.len().saturating_sub(1),
+            determined: !row.is_empty(),
+            differences,
+        })
+    }
+
+    // The value at `index`: the length of the sequence for the next value,
+    // -1 for the one before the first. None when it does not fit.
+    pub(super) fn at(&self, index: Wide) -> Option<Wide> {
+        let mut value: Wide = 0;
+        // C(index, k) = C(index, k - 1) * (index - k + 1) / k. Whatever part
+        // of k does not divide C(index, k - 1) divides index - k + 1, so both
+        // are divided first and the product only overflows when C(index, k)
+        // itself does.
+        let mut binomial: Wide = 1;
+        for (k, d) in self.differences.iter().enumerate() {
+            if k > 0 {
+                let k = k as Wide;
+                let common = gcd(binomial, k);
+                binomial =
+                    (binomial / common).checked_mul(index.checked_sub(k - 1)? / (k / common))?;
+            }
+            value = value.checked_add(d.checked_mul(binomial)?)?;
+        }
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn check_fit_and_extrapolate() {
+        let squares = Extrapolation::fit(&[1, 4, 9, 16]).unwrap();
+        assert_eq!((2, true), (squares.degree, squares.determined));
+        assert_eq!(Some(25), squares.at(4));
+        assert_eq!(Some(0), squares.at(-1));
+        assert_eq!(Some(10_000), squares.at(99));
+        assert_eq!(Some(81), squares.at(-10));
+
+        // the cubic through four points, not confirmed by a zero row
+        let guess = Extrapolation::fit(&[0, 0, 0, 6]).unwrap();
+        assert_eq!((3, false), (guess.degree, guess.determined));
+        assert_eq!(Some(24), guess.at(4));
+
+        let zeros = Extrapolation::fit(&[0, 0]).unwrap();
+        assert_eq!(
+            (0, true, Some(0)),
+            (zeros.degree, zeros.determined, zeros.at(7))
+        );
+        let single = Extrapolation::fit(&[5]).unwrap();
+        assert_eq!(
+            (0, false, Some(5)),
+            (single.degree, single.determined, single.at(-3))
+        );
+        assert_eq!(None, Extrapolation::fit(&[]));
+        // C(x, 2) fits while x * (x - 1) does not
+        let pairs = Extrapolation::fit(&[0, 0, 1]).unwrap();
+        assert_eq!(
+            Some(7_500_000_000_000_000_000 * 14_999_999_999_999_999_999),
+            pairs.at(15_000_000_000_000_000_000)
+        );
+        assert_eq!(
+            None,
+            Extrapolation::fit(&[0, i64::MAX, 0, i64::MIN, 0])
+                .unwrap()
+                .at(Wide::MAX)
+        );
+    }
+}
@@ -1,77 +1,142 @@
-use std::fs::read_to_string;
+mod extrapolate;
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
-    }
-    result
+use common::error::{overflow, parse_number, InputError};
+use common::exact::Wide;
+use common::input::read_input;
+use extrapolate::Extrapolation;
+
+fn allowed(c: char) -> bool {
+    c.is_ascii_digit() || " -".contains(c)
 }
-fn parse_input(input: &Vec<String>) -> Vec<Vec<i64>> {
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
+}
+fn parse_input(input: &Vec<String>) -> Result<Vec<Vec<i64>>, InputError> {
     input
         .iter()
-        .map(|line| {
+        .enumerate()
+        .map(|(i, line)| {
             line.split(" ")
-                .map(|number| number.parse::<i64>().unwrap())
-                .collect::<Vec<i64>>()
+                .map(|number| parse_number::<i64>(number, i))
+                .collect::<Result<Vec<i64>, InputError>>()
         })
-        .collect::<Vec<Vec<i64>>>()
+        .collect::<Result<Vec<Vec<i64>>, InputError>>()
 }
 
-fn find_next(seq: &Vec<i64>) -> i64 {
-    let mut last_diagonal: Vec<i64> = vec![seq.last().unwrap().clone()];
-    let mut temp: Vec<i64> = seq.clone();
-    while !temp.iter().all(|a| *a == 0) {
-        temp = temp
-            .windows(2)
-            .map(|window| window[1] - window[0])
-            .collect::<Vec<i64>>();
-        last_diagonal.push(temp.last().unwrap().clone());
-    }
-    last_diagonal.iter().rev().fold(0, |acc, x| x + acc)
+fn fit(seq: &[i64]) -> Result<Extrapolation, InputError> {
+    Extrapolation::fit(seq).ok_or_else(overflow)
 }
 
-fn solve_part_a(input: &Vec<String>) -> i64 {
-    parse_input(input)
+// Sum of the values at `index(length)` over all sequences.
+fn sum_at(input: &Vec<String>, index: impl Fn(usize) -> Wide) -> Result<i64, InputError> {
+    parse_input(input)?
         .iter()
-        .map(|seq| find_next(seq))
-        .collect::<Vec<i64>>()
+        .map(|seq| fit(seq)?.at(index(seq.len())).ok_or_else(overflow))
+        .collect::<Result<Vec<Wide>, InputError>>()?
         .iter()
-        .sum()
+        .try_fold(0 as Wide, |acc, x| acc.checked_add(*x))
+        .and_then(|sum| i64::try_from(sum).ok())
+        .ok_or_else(overflow)
 }
 
-fn solve_part_b(input: &Vec<String>) -> i64 {
-    parse_input(input)
-        .into_iter()
-        .map(|mut seq| {
-            seq.reverse();
-            find_next(&seq)
-        })
-        .collect::<Vec<i64>>()
-        .iter()
-        .sum()
+fn solve_part_a(input: &Vec<String>) -> Result<i64, InputError> {
+    sum_at(input, |length| length as Wide)
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: i64 = solve_part_a(&input);
-    let result_part_b: i64 = solve_part_b(&input);
+fn solve_part_b(input: &Vec<String>) -> Result<i64, InputError> {
+    sum_at(input, |_| -1)
+}
+
+// Tab separated, one row per sequence: its degree and the value `steps`
+// past the last value, or before the first one when `steps` is negative.
+fn report_table(input: &Vec<String>, steps: i64) -> Result<String, InputError> {
+    let mut table = String::from("line\tlength\tdegree\tdetermined\tindex\tvalue\n");
+    for (i, seq) in parse_input(input)?.iter().enumerate() {
+        let extrapolation = fit(seq)?;
+        let index = match steps {
+            0.. => seq.len() as Wide - 1 + steps as Wide,
+            _ => steps as Wide,
+        };
+        table += &format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\n",
+            i + 1,
+            seq.len(),
+            extrapolation.degree,
+            extrapolation.determined,
+            index,
+            extrapolation.at(index).ok_or_else(overflow)?
+        );
+    }
+    Ok(table)
+}
+
+// `cargo run -- --report <steps>` prints each sequence's degree and its value
+// that many steps forward (or backward, when negative) instead of the totals.
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(i) = args.iter().position(|a| a == "--report") {
+        let steps = match args.get(i + 1) {
+            Some(steps) => parse_number::<i64>(steps, 0)
+                .map_err(|_| InputError::new("--report takes a number of steps"))?,
+            None => 1,
+        };
+        print!("{}", report_table(&input, steps)?);
+        return Ok(());
+    }
+    let result_part_a: i64 = solve_part_a(&input)?;
+    let result_part_b: i64 = solve_part_b(&input)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(114, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(114), solve_part_a(&example));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(2, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(2), solve_part_b(&example));
+    }
+
+    #[test]
+    fn check_report() {
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(
+            Ok("line\tlength\tdegree\tdetermined\tindex\tvalue\n\
+                1\t6\t1\ttrue\t7\t21\n\
+                2\t6\t2\ttrue\t7\t36\n\
+                3\t6\t3\ttrue\t7\t101\n"
+                .to_string()),
+            report_table(&example, 2)
+        );
+        let backward = report_table(&example, -1).unwrap();
+        let values: Vec<&str> = backward
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.rsplit('\t').next())
+            .collect();
+        assert_eq!(vec!["-3", "0", "5"], values);
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(
+            &["0 3 6 9 12 15\n10 13 16 21 30 45\n-4 -2\n"],
+            allowed,
+            |input| {
+                let _ = solve_part_a(input);
+                let _ = solve_part_b(input);
+            },
+        );
     }
 }
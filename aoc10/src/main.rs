@@ -1,18 +1,32 @@
-use core::panic;
-use std::{collections::HashMap, collections::HashSet, fs::read_to_string};
+use common::config::Config;
+use common::error::InputError;
+use common::input::read_input;
+use std::{collections::HashMap, collections::HashSet, fmt};
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
-    }
-    result
+fn allowed(c: char) -> bool {
+    "|-LJ7F.S".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
 }
-fn parse_input(input: &Vec<String>) -> Vec<Vec<String>> {
-    input
+fn parse_input(input: &Vec<String>) -> Result<Vec<Vec<String>>, InputError> {
+    let width = input.first().map_or(0, |line| line.len());
+    for (i, line) in input.iter().enumerate() {
+        if let Some(c) = line.chars().find(|c| !"|-LJ7F.S".contains(*c)) {
+            return Err(InputError::at(i, format!("unknown tile `{}`", c)));
+        }
+        if line.len() != width {
+            return Err(InputError::at(
+                i,
+                format!("expected {} tiles per row", width),
+            ));
+        }
+    }
+    Ok(input
         .iter()
         .map(|line| line.chars().map(|s| s.to_string()).collect::<Vec<String>>())
-        .collect::<Vec<Vec<String>>>()
+        .collect::<Vec<Vec<String>>>())
 }
 fn build_map(space_array: &Vec<Vec<String>>) -> HashMap<(usize, usize), String> {
     let mut map: HashMap<(usize, usize), String> = HashMap::new();
@@ -23,79 +37,156 @@ fn build_map(space_array: &Vec<Vec<String>>) -> HashMap<(usize, usize), String>
     }
     map
 }
-fn starting_point(map: &HashMap<(usize, usize), String>) -> (usize, usize) {
-    let (point, _) = map
+fn starting_point(map: &HashMap<(usize, usize), String>) -> Result<(usize, usize), InputError> {
+    let starts = map
         .iter()
         .filter(|(_, v)| *v == "S")
-        .nth(0)
-        .unwrap()
-        .clone();
-    *point
+        .map(|(k, _)| *k)
+        .collect::<Vec<(usize, usize)>>();
+    match starts.as_slice() {
+        [point] => Ok(*point),
+        _ => Err(InputError::new("expected exactly one S tile")),
+    }
 }
 
+// Directions by index, as moves in (row, column).
+const EAST: usize = 0;
+const SOUTH: usize = 1;
+const WEST: usize = 2;
+const NORTH: usize = 3;
+const STEPS: [(i64, i64); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+
+// Every pipe and the two directions it opens to.
+const PIPES: [(&str, [usize; 2]); 6] = [
+    ("|", [NORTH, SOUTH]),
+    ("-", [EAST, WEST]),
+    ("L", [NORTH, EAST]),
+    ("J", [NORTH, WEST]),
+    ("7", [SOUTH, WEST]),
+    ("F", [SOUTH, EAST]),
+];
+
+fn openings(tile: &str) -> Option<[usize; 2]> {
+    PIPES.iter().find(|(t, _)| *t == tile).map(|(_, o)| *o)
+}
+
+fn pipe(openings: [usize; 2]) -> &'static str {
+    PIPES
+        .iter()
+        .find(|(_, o)| o.contains(&openings[0]) && o.contains(&openings[1]))
+        .map(|(t, _)| *t)
+        .unwrap_or(".")
+}
+
+fn opposite(direction: usize) -> usize {
+    (direction + 2) % 4
+}
+
+fn neighbor(point: (usize, usize), direction: usize) -> Option<(usize, usize)> {
+    let (dx, dy) = STEPS[direction];
+    Some((
+        point.0.checked_add_signed(dx as isize)?,
+        point.1.checked_add_signed(dy as isize)?,
+    ))
+}
+
+// Walks the loop leaving S through one of `shape`'s openings, which has to
+// bring us back in through the other. The tiles come in walking order,
+// starting with S.
 fn find_loop(
     starting_point: (usize, usize),
+    shape: [usize; 2],
     map: &HashMap<(usize, usize), String>,
-) -> HashSet<(usize, usize)> {
-    let steps = [(0, 1), (1, 0), (0, -1), (-1, 0)];
-    let mut loop_points: HashSet<(usize, usize)> = HashSet::new();
-    let (mut cur_x, mut cur_y) = (starting_point.0 + 1, starting_point.1);
-    let mut direction: usize = 1;
-    loop_points.insert((cur_x, cur_y));
-    while (cur_x, cur_y) != starting_point {
-        match map.get(&(cur_x, cur_y)).unwrap().as_str() {
-            "J" => {
-                if direction == 0 {
-                    direction = 3;
-                } else if direction == 1 {
-                    direction = 2;
-                }
-            }
-            "|" => {}
-            "F" => {
-                if direction == 3 {
-                    direction = 0;
-                } else if direction == 2 {
-                    direction = 1;
-                }
-            }
-            "-" => {}
-            "L" => {
-                if direction == 1 {
-                    direction = 0;
-                } else if direction == 2 {
-                    direction = 3;
-                }
+) -> Result<Vec<(usize, usize)>, InputError> {
+    let mut path: Vec<(usize, usize)> = vec![starting_point];
+    let mut seen: HashSet<(usize, usize)> = HashSet::from([starting_point]);
+    let mut current = starting_point;
+    let mut direction = shape[0];
+    loop {
+        current = neighbor(current, direction)
+            .filter(|point| map.contains_key(point))
+            .ok_or_else(|| InputError::new("the loop runs off the map"))?;
+        let from = opposite(direction);
+        if current == starting_point {
+            if from == shape[1] {
+                return Ok(path);
             }
-            "7" => {
-                if direction == 0 {
-                    direction = 1;
-                } else if direction == 3 {
-                    direction = 2;
-                }
+            return Err(InputError::new("the loop does not close at S"));
+        }
+        // every pipe can only be entered from its two open ends
+        let [a, b] = openings(&map[&current])
+            .filter(|o| o.contains(&from) && seen.insert(current))
+            .ok_or_else(|| {
+                InputError::new(format!(
+                    "the loop from S breaks at row {}, column {}",
+                    current.0 + 1,
+                    current.1 + 1
+                ))
+            })?;
+        path.push(current);
+        direction = if a == from { b } else { a };
+    }
+}
+
+// The pipe under S: of the neighbors with a pipe opening towards S, the pair
+// that closes a loop through it.
+fn start_shape(
+    starting_point: (usize, usize),
+    map: &HashMap<(usize, usize), String>,
+) -> Result<[usize; 2], InputError> {
+    let connected: Vec<usize> = (0..4)
+        .filter(|d| {
+            neighbor(starting_point, *d)
+                .and_then(|point| openings(map.get(&point)?))
+                .is_some_and(|o| o.contains(&opposite(*d)))
+        })
+        .collect();
+    if connected.len() < 2 {
+        return Err(InputError::new(format!(
+            "S connects to {} pipe(s), a loop needs two",
+            connected.len()
+        )));
+    }
+    let mut shapes: Vec<[usize; 2]> = Vec::new();
+    for (i, first) in connected.iter().enumerate() {
+        for second in &connected[i + 1..] {
+            if find_loop(starting_point, [*first, *second], map).is_ok() {
+                shapes.push([*first, *second]);
             }
-            _ => panic!(),
         }
-        let (next_x, next_y) = steps[direction];
-        (cur_x, cur_y) = (
-            (cur_x as i64 + next_x) as usize,
-            (cur_y as i64 + next_y) as usize,
-        );
-        loop_points.insert((cur_x, cur_y));
     }
-    loop_points
+    match shapes[..] {
+        [shape] => Ok(shape),
+        [] => Err(InputError::new("no loop runs through S")),
+        _ => Err(InputError::new(format!(
+            "S is ambiguous, it could be any of {}",
+            shapes
+                .iter()
+                .map(|s| pipe(*s))
+                .collect::<Vec<&str>>()
+                .join(" ")
+        ))),
+    }
 }
-fn solve_part_a(input: &Vec<String>) -> u64 {
-    let map = build_map(&parse_input(input));
-    let (x, y) = starting_point(&map);
-    let length = find_loop((x, y), &map).len();
-    length as u64 / 2
+
+// S, the pipe under it and the loop through it.
+type MainLoop = ((usize, usize), [usize; 2], Vec<(usize, usize)>);
+
+fn main_loop(map: &HashMap<(usize, usize), String>) -> Result<MainLoop, InputError> {
+    let start = starting_point(map)?;
+    let shape = start_shape(start, map)?;
+    Ok((start, shape, find_loop(start, shape, map)?))
+}
+
+fn solve_part_a(input: &Vec<String>) -> Result<u64, InputError> {
+    let map = build_map(&parse_input(input)?);
+    let (_, _, path) = main_loop(&map)?;
+    Ok(path.len() as u64 / 2)
 }
 
-fn solve_part_b(input: &Vec<String>) -> i64 {
-    let map = build_map(&parse_input(input));
-    let (x, y) = starting_point(&map);
-    let loop_points = find_loop((x, y), &map);
+// Enclosed tiles by casting a ray from every tile off the loop and counting
+// the loop crossings on the way.
+fn ray_cast(map: &HashMap<(usize, usize), String>, loop_points: &HashSet<(usize, usize)>) -> i64 {
     let mut space_counter = 0;
 
     // go through all the points not in the loop
@@ -105,11 +196,7 @@ fn solve_part_b(input: &Vec<String>) -> i64 {
         let mut prev_character = "".to_string();
 
         (0..*x).for_each(|i| {
-            let mut character = map.get(&(i, *y)).unwrap().clone();
-            // special condition for S could different on other inputs
-            if character == "S".to_string() {
-                character = "7".to_string();
-            }
+            let character = map.get(&(i, *y)).unwrap().clone();
             if loop_points.contains(&(i, *y)) {
                 // dont double count edge cases
                 if prev_character == "F" && character == "J"
@@ -135,26 +222,416 @@ fn solve_part_b(input: &Vec<String>) -> i64 {
     space_counter
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input);
-    let result_part_b: i64 = solve_part_b(&input);
+// Enclosed tiles from the loop alone. With the tile centers as lattice points
+// the loop is a polygon whose area the shoelace formula gives, and Pick's
+// theorem, area = inside + boundary / 2 - 1, turns that into the number of
+// points strictly inside.
+fn shoelace(path: &[(usize, usize)]) -> i64 {
+    let twice_area: i64 = path
+        .iter()
+        .zip(path.iter().cycle().skip(1))
+        .map(|(a, b)| a.0 as i64 * b.1 as i64 - b.0 as i64 * a.1 as i64)
+        .sum::<i64>()
+        .abs();
+    (twice_area - path.len() as i64) / 2 + 1
+}
+
+// (rows, columns)
+fn dimensions(map: &HashMap<(usize, usize), String>) -> (usize, usize) {
+    map.keys()
+        .fold((0, 0), |(h, w), (x, y)| (h.max(x + 1), w.max(y + 1)))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Region {
+    Loop,
+    Inside,
+    Outside,
+}
+
+// Flood fill from the border on a grid of twice the resolution, where tile
+// (x, y) sits at (2x + 1, 2y + 1) and the points in between are the gaps
+// between tiles. The loop walls off its tiles and the gaps between
+// consecutive loop tiles, so the fill squeezes between pipes that run side by
+// side but are not connected.
+fn regions(map: &HashMap<(usize, usize), String>, path: &[(usize, usize)]) -> Vec<Vec<Region>> {
+    let (height, width) = dimensions(map);
+    let (rows, columns) = (2 * height + 1, 2 * width + 1);
+    let mut blocked = vec![vec![false; columns]; rows];
+    for (a, b) in path.iter().zip(path.iter().cycle().skip(1)) {
+        blocked[2 * a.0 + 1][2 * a.1 + 1] = true;
+        blocked[a.0 + b.0 + 1][a.1 + b.1 + 1] = true;
+    }
+    let mut outside = vec![vec![false; columns]; rows];
+    let mut queue: Vec<(usize, usize)> = vec![(0, 0)];
+    outside[0][0] = true;
+    while let Some(point) = queue.pop() {
+        for direction in 0..4 {
+            if let Some((x, y)) = neighbor(point, direction)
+                .filter(|(x, y)| *x < rows && *y < columns && !blocked[*x][*y])
+            {
+                if !outside[x][y] {
+                    outside[x][y] = true;
+                    queue.push((x, y));
+                }
+            }
+        }
+    }
+    (0..height)
+        .map(|x| {
+            (0..width)
+                .map(
+                    |y| match (blocked[2 * x + 1][2 * y + 1], outside[2 * x + 1][2 * y + 1]) {
+                        (true, _) => Region::Loop,
+                        (false, true) => Region::Outside,
+                        (false, false) => Region::Inside,
+                    },
+                )
+                .collect()
+        })
+        .collect()
+}
+
+// The map with every tile off the main loop turned into ground, or into `I`
+// and `O` for inside and outside with `mark_regions`.
+fn clean_map(
+    map: &HashMap<(usize, usize), String>,
+    path: &[(usize, usize)],
+    mark_regions: bool,
+) -> Vec<String> {
+    regions(map, path)
+        .iter()
+        .enumerate()
+        .map(|(x, row)| {
+            row.iter()
+                .enumerate()
+                .map(|(y, region)| match (region, mark_regions) {
+                    (Region::Loop, _) => map[&(x, y)].as_str(),
+                    (_, false) => ".",
+                    (Region::Inside, true) => "I",
+                    (Region::Outside, true) => "O",
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Problems with the map, for `--diagnose`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Diagnostic {
+    // there is no loop through S, why
+    NoLoop(String),
+    // S has more than two pipes pointing at it
+    Branch {
+        tile: (usize, usize),
+        pipes: usize,
+    },
+    // a pipe end that no pipe connects back to
+    Dangling {
+        tile: (usize, usize),
+        direction: usize,
+    },
+    // a closed loop of pipes other than the one through S
+    ExtraLoop {
+        tile: (usize, usize),
+        length: usize,
+    },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let at = |tile: &(usize, usize)| format!("row {}, column {}", tile.0 + 1, tile.1 + 1);
+        match self {
+            Diagnostic::NoLoop(reason) => write!(f, "no loop through S: {}", reason),
+            Diagnostic::Branch { tile, pipes } => {
+                write!(f, "{}: S has {} pipes pointing at it", at(tile), pipes)
+            }
+            Diagnostic::Dangling { tile, direction } => write!(
+                f,
+                "{}: pipe open to the {} with nothing connecting back",
+                at(tile),
+                ["east", "south", "west", "north"][*direction]
+            ),
+            Diagnostic::ExtraLoop { tile, length } => {
+                write!(f, "{}: a separate loop of {} pipes", at(tile), length)
+            }
+        }
+    }
+}
+
+fn diagnose(map: &HashMap<(usize, usize), String>) -> Result<Vec<Diagnostic>, InputError> {
+    let start = starting_point(map)?;
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    // S with the shape it has, or open to every side when it has none so its
+    // neighbors do not count as dangling because of it
+    let open = |tile: (usize, usize)| -> Vec<usize> {
+        match map.get(&tile).map(|t| t.as_str()) {
+            Some("S") => (0..4).collect(),
+            Some(t) => openings(t).map_or(Vec::new(), |o| o.to_vec()),
+            None => Vec::new(),
+        }
+    };
+    let pipes_at_start = (0..4)
+        .filter(|d| {
+            neighbor(start, *d).is_some_and(|n| {
+                map.get(&n)
+                    .and_then(|t| openings(t))
+                    .is_some_and(|o| o.contains(&opposite(*d)))
+            })
+        })
+        .count();
+    if pipes_at_start > 2 {
+        diagnostics.push(Diagnostic::Branch {
+            tile: start,
+            pipes: pipes_at_start,
+        });
+    }
+    let main = match main_loop(map) {
+        Ok((_, _, path)) => path.into_iter().collect::<HashSet<(usize, usize)>>(),
+        Err(e) => {
+            diagnostics.push(Diagnostic::NoLoop(e.message));
+            HashSet::new()
+        }
+    };
+
+    let mut tiles: Vec<(usize, usize)> = map
+        .iter()
+        .filter(|(_, t)| openings(t).is_some())
+        .map(|(k, _)| *k)
+        .collect();
+    tiles.sort();
+    // pipes connected to each pipe, both ends agreeing
+    let mut connected: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+    for tile in &tiles {
+        for direction in open(*tile) {
+            match neighbor(*tile, direction).filter(|n| open(*n).contains(&opposite(direction))) {
+                Some(n) => connected.entry(*tile).or_default().push(n),
+                None => diagnostics.push(Diagnostic::Dangling {
+                    tile: *tile,
+                    direction,
+                }),
+            }
+        }
+    }
+
+    // closed loops: groups of connected pipes where each has both ends in use
+    let mut seen: HashSet<(usize, usize)> = main.clone();
+    seen.insert(start);
+    for tile in &tiles {
+        if seen.contains(tile) {
+            continue;
+        }
+        let mut group: Vec<(usize, usize)> = vec![*tile];
+        let mut queue: Vec<(usize, usize)> = vec![*tile];
+        seen.insert(*tile);
+        while let Some(current) = queue.pop() {
+            for n in connected.get(&current).into_iter().flatten() {
+                if map[n] != "S" && seen.insert(*n) {
+                    group.push(*n);
+                    queue.push(*n);
+                }
+            }
+        }
+        if group.iter().all(|t| {
+            connected
+                .get(t)
+                .is_some_and(|c| c.len() == 2 && c.iter().all(|n| map[n] != "S"))
+        }) {
+            diagnostics.push(Diagnostic::ExtraLoop {
+                tile: *tile,
+                length: group.len(),
+            });
+        }
+    }
+    Ok(diagnostics)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Method {
+    RayCast,
+    Shoelace,
+    FloodFill,
+}
+
+impl Method {
+    fn parse(text: &str) -> Result<Method, InputError> {
+        match text.trim() {
+            "raycast" => Ok(Method::RayCast),
+            "shoelace" => Ok(Method::Shoelace),
+            "floodfill" => Ok(Method::FloodFill),
+            other => Err(InputError::new(format!(
+                "expected raycast, shoelace or floodfill, found `{}`",
+                other
+            ))),
+        }
+    }
+}
+
+fn solve_part_b(input: &Vec<String>, method: Method) -> Result<i64, InputError> {
+    let mut map = build_map(&parse_input(input)?);
+    let (start, shape, path) = main_loop(&map)?;
+    map.insert(start, pipe(shape).to_string());
+    Ok(match method {
+        Method::RayCast => ray_cast(&map, &path.iter().copied().collect()),
+        Method::Shoelace => shoelace(&path),
+        Method::FloodFill => regions(&map, &path)
+            .iter()
+            .flatten()
+            .filter(|r| **r == Region::Inside)
+            .count() as i64,
+    })
+}
+
+// Instead of the totals: `cargo run -- --cross-check` counts part B every way
+// and fails when they disagree, `--clean` prints the map with only the main
+// loop left, `--regions` the same with inside and outside marked, and
+// `--diagnose` what is wrong with the map.
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = Config::from_args("./config.toml", args.clone().into_iter())?;
+    if args.iter().any(|a| a == "--cross-check") {
+        let ray_cast = solve_part_b(&input, Method::RayCast)?;
+        let shoelace = solve_part_b(&input, Method::Shoelace)?;
+        let flood_fill = solve_part_b(&input, Method::FloodFill)?;
+        println!("Ray casting: {}", ray_cast);
+        println!("Shoelace: {}", shoelace);
+        println!("Flood fill: {}", flood_fill);
+        if ray_cast != shoelace || shoelace != flood_fill {
+            return Err(InputError::new("the enclosed tile counts disagree"));
+        }
+        return Ok(());
+    }
+    if let Some(flag) = args.iter().find(|a| *a == "--clean" || *a == "--regions") {
+        let map = build_map(&parse_input(&input)?);
+        let (_, _, path) = main_loop(&map)?;
+        for line in clean_map(&map, &path, flag == "--regions") {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+    if args.iter().any(|a| a == "--diagnose") {
+        let diagnostics = diagnose(&build_map(&parse_input(&input)?))?;
+        for diagnostic in &diagnostics {
+            println!("{}", diagnostic);
+        }
+        if diagnostics.is_empty() {
+            println!("No problems found");
+        }
+        return Ok(());
+    }
+    let method = Method::parse(&config.get("enclosed", "raycast".to_string())?)?;
+    let result_part_a: u64 = solve_part_a(&input)?;
+    let result_part_b: i64 = solve_part_b(&input, method)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example-a.txt");
-        assert_eq!(8, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example-a.txt").unwrap();
+        assert_eq!(Ok(8), solve_part_a(&example));
     }
 
     #[test]
     fn check_part_b_example1() {
-        let example: Vec<String> = read_lines("./inputs/example-b.txt");
-        assert_eq!(10, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example-b.txt").unwrap();
+        assert_eq!(Ok(10), solve_part_b(&example, Method::RayCast));
+        assert_eq!(Ok(10), solve_part_b(&example, Method::Shoelace));
+        assert_eq!(Ok(10), solve_part_b(&example, Method::FloodFill));
+    }
+
+    #[test]
+    fn check_start_shape() {
+        let shape = |rows: &[&str]| {
+            let map =
+                build_map(&parse_input(&rows.iter().map(|r| r.to_string()).collect()).unwrap());
+            start_shape(starting_point(&map).unwrap(), &map).map(pipe)
+        };
+        assert_eq!(
+            Ok("F"),
+            shape(&["..F7.", ".FJ|.", "SJ.L7", "|F--J", "LJ..."])
+        );
+        // three pipes point at S but only west and south close a loop
+        assert_eq!(Ok("7"), shape(&["F-S-7", "|.|.|", "L-J.|"]));
+        assert!(shape(&["F7..", "LS-7", ".L-J"])
+            .unwrap_err()
+            .message
+            .contains("ambiguous"));
+        assert!(shape(&["S-7", "..|", "..."]).is_err());
+        assert!(shape(&["F-S", "|.|", "L-."]).is_err());
+    }
+
+    #[test]
+    fn check_regions_and_cleanup() {
+        let lines = |rows: &[&str]| rows.iter().map(|r| r.to_string()).collect::<Vec<String>>();
+        // the gap between the two middle columns is outside, reached by
+        // squeezing between the pipes at the bottom
+        let squeeze = lines(&[
+            "..........",
+            ".S------7.",
+            ".|F----7|.",
+            ".||....||.",
+            ".||....||.",
+            ".|L-7F-J|.",
+            ".|..||..|.",
+            ".L--JL--J.",
+            "..........",
+        ]);
+        assert_eq!(Ok(4), solve_part_b(&squeeze, Method::FloodFill));
+        assert_eq!(Ok(4), solve_part_b(&squeeze, Method::Shoelace));
+
+        let map = build_map(&parse_input(&lines(&["F-7F7.", "|.|LJ.", "S-J..|"])).unwrap());
+        let (_, _, path) = main_loop(&map).unwrap();
+        assert_eq!(
+            lines(&["F-7...", "|.|...", "S-J..."]),
+            clean_map(&map, &path, false)
+        );
+        assert_eq!(
+            lines(&["F-7OOO", "|I|OOO", "S-JOOO"]),
+            clean_map(&map, &path, true)
+        );
+        assert_eq!(
+            Ok(vec![Diagnostic::ExtraLoop {
+                tile: (0, 3),
+                length: 4
+            },]),
+            diagnose(&map).map(|d| d
+                .into_iter()
+                .filter(|d| matches!(d, Diagnostic::ExtraLoop { .. }))
+                .collect::<Vec<Diagnostic>>())
+        );
+        assert_eq!(
+            "row 3, column 6: pipe open to the north with nothing connecting back",
+            diagnose(&map).unwrap()[0].to_string()
+        );
+        let broken = build_map(&parse_input(&lines(&["F7..", "LS-7", ".L-J"])).unwrap());
+        let diagnostics = diagnose(&broken).unwrap();
+        assert_eq!(
+            Diagnostic::Branch {
+                tile: (1, 1),
+                pipes: 4
+            },
+            diagnostics[0]
+        );
+        assert!(matches!(diagnostics[1], Diagnostic::NoLoop(_)));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(&["..F7.\n.FJ|.\nSJ.L7\n|F--J\nLJ...\n"], allowed, |input| {
+            let _ = solve_part_a(input);
+            let ray_cast = solve_part_b(input, Method::RayCast);
+            assert_eq!(ray_cast, solve_part_b(input, Method::Shoelace));
+            assert_eq!(ray_cast, solve_part_b(input, Method::FloodFill));
+            if let Ok(map) = parse_input(input).map(|grid| build_map(&grid)) {
+                let _ = diagnose(&map);
+            }
+        });
     }
 }
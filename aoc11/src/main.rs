@@ -1,133 +1,151 @@
-use std::{collections::HashMap, collections::HashSet, fs::read_to_string};
+use common::config::Config;
+use common::error::{overflow, InputError};
+use common::exact::Wide;
+use common::input::read_input;
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
-    }
-    result
-}
-fn parse_input(input: &Vec<String>) -> Vec<Vec<String>> {
-    input
-        .iter()
-        .map(|line| line.chars().map(|s| s.to_string()).collect::<Vec<String>>())
-        .collect::<Vec<Vec<String>>>()
+fn allowed(c: char) -> bool {
+    ".#".contains(c)
 }
-fn find_expansion_lines(space_array: &Vec<Vec<String>>) -> (Vec<usize>, Vec<usize>) {
-    let mut x: Vec<usize> = Vec::new();
-    let mut y: Vec<usize> = Vec::new();
 
-    for i in 0..space_array.len() {
-        if space_array[i].iter().all(|c| c != "#") {
-            x.push(i);
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
+}
+fn parse_input(input: &Vec<String>) -> Result<Vec<Vec<String>>, InputError> {
+    let width = match input.first() {
+        Some(line) if !line.is_empty() => line.len(),
+        _ => return Err(InputError::new("the image is empty")),
+    };
+    for (i, line) in input.iter().enumerate() {
+        if let Some(c) = line.chars().find(|c| *c != '.' && *c != '#') {
+            return Err(InputError::at(i, format!("unknown pixel `{}`", c)));
         }
-    }
-    for i in 0..space_array[0].len() {
-        if space_array.iter().all(|x| x[i] != "#") {
-            y.push(i);
+        if line.len() != width {
+            return Err(InputError::at(
+                i,
+                format!("expected {} pixels per row", width),
+            ));
         }
     }
-    (x, y)
+    Ok(input
+        .iter()
+        .map(|line| line.chars().map(|s| s.to_string()).collect::<Vec<String>>())
+        .collect::<Vec<Vec<String>>>())
 }
-
-fn find_galaxies(map: &Vec<Vec<String>>) -> HashSet<(usize, usize)> {
-    let mut galaxies: HashSet<(usize, usize)> = HashSet::new();
-    map.iter().enumerate().for_each(|(i, row)| {
-        row.iter().enumerate().for_each(|(j, galaxy)| {
-            if galaxy == "#" {
-                galaxies.insert((i, j));
+// Galaxies on every row and on every column.
+fn galaxy_counts(map: &[Vec<String>]) -> (Vec<u64>, Vec<u64>) {
+    let mut rows: Vec<u64> = vec![0; map.len()];
+    let mut columns: Vec<u64> = vec![0; map.first().map_or(0, |row| row.len())];
+    for (i, row) in map.iter().enumerate() {
+        for (j, pixel) in row.iter().enumerate() {
+            if pixel == "#" {
+                rows[i] += 1;
+                columns[j] += 1;
             }
-        })
-    });
-    galaxies
+        }
+    }
+    (rows, columns)
 }
 
-fn find_lengths(
-    galaxies: &HashSet<(usize, usize)>,
-    expansion_lines: (Vec<usize>, Vec<usize>),
-    expansion_coefficient: u64,
-) -> Vec<u64> {
-    let mut temp: HashMap<(usize, usize, usize, usize), u64> = HashMap::new();
-    for (i_x, i_y) in galaxies {
-        for (j_x, j_y) in galaxies {
-            let ix = *i_x;
-            let iy = *i_y;
-            let jx = *j_x;
-            let jy = *j_y;
-
-            if ix != jx || iy != jy {
-                if temp.contains_key(&(ix, iy, jx, jy)) || temp.contains_key(&(jx, jy, ix, iy)) {
-                    continue;
-                } else {
-                    let mut counter_x = 0;
-                    let mut counter_y = 0;
-                    let (x, y) = &expansion_lines;
-                    //check how many times we cross a expansion line in x and y direction
-                    for k in x {
-                        if (ix..jx).contains(&k) || (jx..ix).contains(&k) {
-                            counter_x += 1;
-                        }
-                    }
-                    for k in y {
-                        if (iy..jy).contains(&k) || (jy..iy).contains(&k) {
-                            counter_y += 1;
-                        }
-                    }
-                    // manhatan distance + the expended lines crossed * the coefficient
-                    temp.insert(
-                        (ix, iy, jx, jy),
-                        (ix as i64).abs_diff(jx as i64)
-                            + (iy as i64).abs_diff(jy as i64)
-                            + ((counter_x + counter_y) * (expansion_coefficient - 1)),
-                    );
-                }
-            }
+// Sum of the distances along one axis over all pairs of galaxies, `counts[i]`
+// of them on line i. Every empty line before a line moves it `extra` further
+// out. The lines come in order, so each galaxy is `position - p` away from an
+// earlier one at `p`, and over all earlier ones that is
+// `seen * position - sum of their positions`.
+fn axis_distance(counts: &[u64], extra: Wide) -> Option<Wide> {
+    let (mut total, mut seen, mut sum, mut empty): (Wide, Wide, Wide, Wide) = (0, 0, 0, 0);
+    for (i, n) in counts.iter().enumerate() {
+        if *n == 0 {
+            empty += 1;
+            continue;
         }
+        let n = *n as Wide;
+        let position = (i as Wide).checked_add(extra.checked_mul(empty)?)?;
+        let distances = seen.checked_mul(position)?.checked_sub(sum)?;
+        total = total.checked_add(n.checked_mul(distances)?)?;
+        seen += n;
+        sum = sum.checked_add(n.checked_mul(position)?)?;
     }
-    temp.iter().map(|(_, v)| *v).collect::<Vec<u64>>()
+    Some(total)
 }
 
-fn solve_part_a(input: &Vec<String>) -> u64 {
-    let map = parse_input(input);
-    let expansion_lines = find_expansion_lines(&map);
-    let galaxies = find_galaxies(&map);
-    let lengths = find_lengths(&galaxies, expansion_lines, 2);
-    lengths.iter().sum()
+fn total_length(input: &Vec<String>, expansion_coefficient: u64) -> Result<u64, InputError> {
+    let extra = expansion_coefficient
+        .checked_sub(1)
+        .ok_or_else(|| InputError::new("the expansion coefficient must be at least 1"))?;
+    let (rows, columns) = galaxy_counts(&parse_input(input)?);
+    axis_distance(&rows, extra as Wide)
+        .zip(axis_distance(&columns, extra as Wide))
+        .and_then(|(x, y)| x.checked_add(y))
+        .and_then(|total| u64::try_from(total).ok())
+        .ok_or_else(overflow)
 }
 
-fn solve_part_b(input: &Vec<String>, coefficient: u64) -> u64 {
-    let map = parse_input(input);
-    let expansion_lines = find_expansion_lines(&map);
-    let galaxies = find_galaxies(&map);
-    let lengths = find_lengths(&galaxies, expansion_lines, coefficient);
-    lengths.iter().sum()
+fn solve_part_a(input: &Vec<String>) -> Result<u64, InputError> {
+    total_length(input, 2)
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input);
-    let result_part_b: u64 = solve_part_b(&input, 1_000_000);
+fn solve_part_b(input: &Vec<String>, coefficient: u64) -> Result<u64, InputError> {
+    total_length(input, coefficient)
+}
+
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let config = Config::from_args("./config.toml", std::env::args().skip(1))?;
+    let result_part_a: u64 = solve_part_a(&input)?;
+    let result_part_b: u64 = solve_part_b(&input, config.get("coefficient", 1_000_000)?)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(374, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(374), solve_part_a(&example));
     }
 
     #[test]
     fn check_part_b_example1() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(1030, solve_part_b(&example, 10));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(1030), solve_part_b(&example, 10));
     }
     #[test]
     fn check_part_b_example2() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(8410, solve_part_b(&example, 100));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(8410), solve_part_b(&example, 100));
+    }
+
+    #[test]
+    fn check_large_coefficients() {
+        let input = vec!["#..".to_string(), "...".to_string(), "..#".to_string()];
+        assert_eq!(
+            Ok(2_000_000_000_002),
+            solve_part_b(&input, 1_000_000_000_000)
+        );
+        assert_eq!(Err(overflow()), solve_part_b(&input, u64::MAX));
+        assert!(solve_part_b(&input, 0).is_err());
+        // many galaxies on a line: 100 on each of two rows 3 apart, all in
+        // different columns
+        let rows: Vec<String> = vec![
+            "#.".repeat(100),
+            ".".repeat(200),
+            ".".repeat(200),
+            ".#".repeat(100),
+        ];
+        // rows: 100 * 100 pairs, 3 apart plus 2 empty rows; columns: one
+        // galaxy on each of 200, C(201, 3) for all pairs
+        assert_eq!(Ok(100 * 100 * 5 + 201 * 200 * 199 / 6), solve_part_a(&rows));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(&["#..\n...\n..#\n"], allowed, |input| {
+            let _ = solve_part_a(input);
+            let _ = solve_part_b(input, 1_000_000);
+        });
     }
 }
@@ -1,73 +1,76 @@
-use std::{collections::HashMap, fs::read_to_string};
+use common::error::{overflow, parse_number, InputError};
+use common::input::read_input;
+use std::collections::HashMap;
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
+fn allowed(c: char) -> bool {
+    c.is_ascii_digit() || ".#?, ".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
+}
+fn parse_line(index: usize, line: &str) -> Result<(String, Vec<usize>), InputError> {
+    let (springs, constraints) = line
+        .split_once(" ")
+        .ok_or_else(|| InputError::at(index, "expected springs and group sizes"))?;
+    if let Some(c) = springs.chars().find(|c| !".#?".contains(*c)) {
+        return Err(InputError::at(index, format!("unknown spring `{}`", c)));
     }
-    result
+    let constraints = constraints
+        .split(",")
+        .map(|c| parse_number::<usize>(c, index))
+        .collect::<Result<Vec<usize>, InputError>>()?;
+    Ok((springs.to_string(), constraints))
 }
-fn parse_input_a(input: &Vec<String>) -> Vec<(String, Vec<usize>)> {
+fn parse_input_a(input: &Vec<String>) -> Result<Vec<(String, Vec<usize>)>, InputError> {
     input
         .iter()
-        .map(|line| {
-            let mut split = line.split(" ");
-            let springs = split.next().unwrap().to_string() + ".";
-            let constraints = split
-                .next()
-                .unwrap()
-                .split(",")
-                .map(|c| c.parse::<usize>().unwrap())
-                .collect::<Vec<usize>>();
-            (springs, constraints)
+        .enumerate()
+        .map(|(i, line)| {
+            let (springs, constraints) = parse_line(i, line)?;
+            Ok((springs + ".", constraints))
         })
-        .collect::<Vec<(String, Vec<usize>)>>()
+        .collect::<Result<Vec<(String, Vec<usize>)>, InputError>>()
 }
-fn parse_input_b(input: &Vec<String>) -> Vec<(String, Vec<usize>)> {
+fn parse_input_b(input: &Vec<String>) -> Result<Vec<(String, Vec<usize>)>, InputError> {
     input
         .iter()
-        .map(|line| {
-            let mut split = line.split(" ");
-            let springs = split.next().unwrap().to_string();
+        .enumerate()
+        .map(|(i, line)| {
+            let (springs, c) = parse_line(i, line)?;
             let mut unfolded = springs.clone();
             for _ in 0..4 {
                 unfolded = unfolded + "?" + &springs;
             }
-            let c = split
-                .next()
-                .unwrap()
-                .split(",")
-                .map(|c| c.parse::<usize>().unwrap())
-                .collect::<Vec<usize>>();
             let unfolded_constaints = [c.as_slice(), &c, &c, &c, &c].concat();
-            (unfolded + ".", unfolded_constaints)
+            Ok((unfolded + ".", unfolded_constaints))
         })
-        .collect::<Vec<(String, Vec<usize>)>>()
+        .collect::<Result<Vec<(String, Vec<usize>)>, InputError>>()
 }
 
 fn count_arrangements(
     spring: &str,
     constraints: &[usize],
     cache: &mut HashMap<String, u64>,
-) -> u64 {
+) -> Option<u64> {
     let key = constraints
         .iter()
         .map(|c| c.to_string())
         .collect::<String>()
         + &spring;
     match cache.get(&key) {
-        Some(result) => return *result,
+        Some(result) => return Some(*result),
         None => (),
     }
 
     if constraints.len() == 0 {
         match spring.find("#") {
-            Some(_) => return 0,
-            None => return 1,
+            Some(_) => return Some(0),
+            None => return Some(1),
         }
     }
     if spring.len() == 0 {
-        return 0;
+        return Some(0);
     }
 
     match &spring[..1] {
@@ -75,19 +78,19 @@ fn count_arrangements(
         "#" => {
             let current_constraint = constraints[0];
             if current_constraint > spring.len() {
-                return 0;
+                return Some(0);
             }
             if !spring[..current_constraint]
                 .chars()
                 .all(|c| c == '#' || c == '?')
             {
-                return 0;
+                return Some(0);
             }
             if spring.len() == current_constraint {
                 if constraints.len() == 1 {
-                    return 1;
+                    return Some(1);
                 } else {
-                    return 0;
+                    return Some(0);
                 }
             }
             if spring[current_constraint..current_constraint + 1]
@@ -95,21 +98,24 @@ fn count_arrangements(
                 .count()
                 != 0
             {
-                let result =
-                    count_arrangements(&spring[current_constraint + 1..], &constraints[1..], cache);
+                let result = count_arrangements(
+                    &spring[current_constraint + 1..],
+                    &constraints[1..],
+                    cache,
+                )?;
                 let key = constraints[1..]
                     .iter()
                     .map(|c| c.to_string())
                     .collect::<String>()
                     + &spring[current_constraint + 1..];
                 cache.insert(key, result);
-                return result;
+                return Some(result);
             } else {
-                return 0;
+                return Some(0);
             }
         }
         "?" => {
-            let result = count_arrangements(&spring[1..], constraints, cache);
+            let result = count_arrangements(&spring[1..], constraints, cache)?;
             let key = constraints
                 .iter()
                 .map(|c| c.to_string())
@@ -121,7 +127,7 @@ fn count_arrangements(
                 &("#".to_string() + &spring[1..].to_string()),
                 constraints,
                 cache,
-            );
+            )?;
             let key = constraints
                 .iter()
                 .map(|c| c.to_string())
@@ -130,59 +136,67 @@ fn count_arrangements(
                 + &spring[1..];
             cache.insert(key, result2);
 
-            return result + result2;
+            return result.checked_add(result2);
         }
         _ => (),
     };
 
-    0
+    Some(0)
 }
 
-fn solve_part_a(input: &Vec<String>) -> u64 {
-    parse_input_a(input)
+fn solve_part_a(input: &Vec<String>) -> Result<u64, InputError> {
+    parse_input_a(input)?
         .iter()
         .map(|(spring, constraints)| {
             let mut cache: HashMap<String, u64> = HashMap::new();
             count_arrangements(&spring, &constraints, &mut cache)
         })
-        .collect::<Vec<u64>>()
-        .iter()
-        .sum()
+        .try_fold(0u64, |sum, count| sum.checked_add(count?))
+        .ok_or_else(overflow)
 }
 
-fn solve_part_b(input: &Vec<String>) -> u64 {
-    parse_input_b(input)
+fn solve_part_b(input: &Vec<String>) -> Result<u64, InputError> {
+    parse_input_b(input)?
         .iter()
         .map(|(spring, c)| {
             let mut cache: HashMap<String, u64> = HashMap::new();
             count_arrangements(&spring, &c, &mut cache)
         })
-        .collect::<Vec<u64>>()
-        .iter()
-        .sum()
+        .try_fold(0u64, |sum, count| sum.checked_add(count?))
+        .ok_or_else(overflow)
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input);
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let result_part_a: u64 = solve_part_a(&input)?;
 
     println!("Part A result: {}", result_part_a);
-    let result_part_b: u64 = solve_part_b(&input);
+    let result_part_b: u64 = solve_part_b(&input)?;
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(21, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(21), solve_part_a(&example));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(525152, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(525152), solve_part_b(&example));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(&["?#?.## 1,2\n.??..?? 1,1\n"], allowed, |input| {
+            let _ = solve_part_a(input);
+            let _ = solve_part_b(input);
+        });
     }
 }
@@ -1,29 +1,48 @@
-use std::fs::read_to_string;
+use common::error::InputError;
+use common::input::read_input;
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
-    }
-    result
+fn allowed(c: char) -> bool {
+    ".#".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
 }
 
-fn parse_input(input: &Vec<String>) -> Vec<Vec<Vec<String>>> {
+fn parse_input(input: &Vec<String>) -> Result<Vec<Vec<Vec<String>>>, InputError> {
     let mut pattern: Vec<Vec<String>> = Vec::new();
     let mut all_patterns: Vec<Vec<Vec<String>>> = Vec::new();
-    for line in input {
-        if line == "" {
+    for (i, line) in input.iter().enumerate() {
+        if line.is_empty() {
+            if pattern.is_empty() {
+                return Err(InputError::at(
+                    i,
+                    "expected a pattern before the blank line",
+                ));
+            }
             all_patterns.push(pattern.clone());
             pattern.clear();
         } else {
+            if let Some(c) = line.chars().find(|c| *c != '.' && *c != '#') {
+                return Err(InputError::at(i, format!("unknown tile `{}`", c)));
+            }
+            if pattern.first().is_some_and(|row| row.len() != line.len()) {
+                return Err(InputError::at(
+                    i,
+                    "rows of a pattern must have the same length",
+                ));
+            }
             pattern.push(line.chars().map(|c| c.to_string()).collect::<Vec<String>>())
         }
     }
+    if pattern.is_empty() {
+        return Err(InputError::new("the input ends without a pattern"));
+    }
     all_patterns.push(pattern);
-    all_patterns
+    Ok(all_patterns)
 }
 
-fn find_reflection(pattern: &Vec<Vec<String>>, errors: u64) -> (u64, u64) {
+fn find_reflection(pattern: &Vec<Vec<String>>, errors: u64) -> Option<(u64, u64)> {
     for row_index in 0..pattern.len() - 1 {
         let remaining = usize::min(row_index + 1, pattern.len() - row_index - 1);
         let mut counter = 0;
@@ -35,7 +54,7 @@ fn find_reflection(pattern: &Vec<Vec<String>>, errors: u64) -> (u64, u64) {
             }
         }
         if counter == errors {
-            return (1, row_index as u64 + 1);
+            return Some((1, row_index as u64 + 1));
         }
     }
 
@@ -50,52 +69,61 @@ fn find_reflection(pattern: &Vec<Vec<String>>, errors: u64) -> (u64, u64) {
             }
         }
         if counter == errors {
-            return (0, column_index as u64 + 1);
+            return Some((0, column_index as u64 + 1));
         }
     }
-    unreachable!()
+    None
 }
 
-fn solve_part_a(input: &Vec<String>) -> u64 {
-    let all_patterns = parse_input(input);
+fn summarize(all_patterns: &[Vec<Vec<String>>], errors: u64) -> Result<u64, InputError> {
     let mut sum = 0;
-    for pattern in &all_patterns {
-        let (direction, number) = find_reflection(pattern, 0);
+    for (i, pattern) in all_patterns.iter().enumerate() {
+        let (direction, number) = find_reflection(pattern, errors).ok_or_else(|| {
+            InputError::new(format!("pattern {} has no line of reflection", i + 1))
+        })?;
         sum += (direction * 99 * number) + number;
     }
-    sum
+    Ok(sum)
 }
 
-fn solve_part_b(input: &Vec<String>) -> u64 {
-    let all_patterns = parse_input(input);
-    let mut sum = 0;
-    for pattern in &all_patterns {
-        let (direction, number) = find_reflection(pattern, 1);
-        sum += (direction * 99 * number) + number;
-    }
-    sum
+fn solve_part_a(input: &Vec<String>) -> Result<u64, InputError> {
+    summarize(&parse_input(input)?, 0)
+}
+
+fn solve_part_b(input: &Vec<String>) -> Result<u64, InputError> {
+    summarize(&parse_input(input)?, 1)
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input);
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let result_part_a: u64 = solve_part_a(&input)?;
     println!("Part A result: {}", result_part_a);
-    let result_part_b: u64 = solve_part_b(&input);
+    let result_part_b: u64 = solve_part_b(&input)?;
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(405, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(405), solve_part_a(&example));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(400, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(400), solve_part_b(&example));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(&["#..#\n.##.\n.##.\n\n##.\n..#\n..#\n"], allowed, |input| {
+            let _ = solve_part_a(input);
+            let _ = solve_part_b(input);
+        });
     }
 }
@@ -1,17 +1,32 @@
-use std::{collections::HashMap, fs::read_to_string};
+use common::config::Config;
+use common::error::InputError;
+use common::input::read_input;
+use std::collections::HashMap;
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
-    }
-    result
+fn allowed(c: char) -> bool {
+    ".#O".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
 }
-fn parse_input(input: &Vec<String>) -> Vec<Vec<String>> {
-    input
+// Spinning rotates the platform in place, so it has to be square.
+fn parse_input(input: &Vec<String>) -> Result<Vec<Vec<String>>, InputError> {
+    for (i, line) in input.iter().enumerate() {
+        if let Some(c) = line.chars().find(|c| !".#O".contains(*c)) {
+            return Err(InputError::at(i, format!("unknown tile `{}`", c)));
+        }
+        if line.len() != input.len() {
+            return Err(InputError::at(
+                i,
+                format!("expected a square platform of {} columns", input.len()),
+            ));
+        }
+    }
+    Ok(input
         .iter()
         .map(|line| line.chars().map(|c| c.to_string()).collect::<Vec<String>>())
-        .collect::<Vec<Vec<String>>>()
+        .collect::<Vec<Vec<String>>>())
 }
 
 fn roll_platform(mut platform: Vec<Vec<String>>) -> Vec<Vec<String>> {
@@ -87,38 +102,49 @@ fn calculate_load(platform: &Vec<Vec<String>>) -> u64 {
     load as u64
 }
 
-fn solve_part_a(input: &Vec<String>) -> u64 {
-    let map = parse_input(input);
+fn solve_part_a(input: &Vec<String>) -> Result<u64, InputError> {
+    let map = parse_input(input)?;
     let roll_map = roll_platform(map);
-    calculate_load(&roll_map)
+    Ok(calculate_load(&roll_map))
 }
 
-fn solve_part_b(input: &Vec<String>) -> u64 {
-    let mut platform = parse_input(input);
-    platform = cycle_platform(platform, 1_000_000_000);
-    calculate_load(&platform)
+fn solve_part_b(input: &Vec<String>, cycles: u64) -> Result<u64, InputError> {
+    let mut platform = parse_input(input)?;
+    platform = cycle_platform(platform, cycles);
+    Ok(calculate_load(&platform))
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input);
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let config = Config::from_args("./config.toml", std::env::args().skip(1))?;
+    let result_part_a: u64 = solve_part_a(&input)?;
     println!("Part A result: {}", result_part_a);
-    let result_part_b: u64 = solve_part_b(&input);
+    let result_part_b: u64 = solve_part_b(&input, config.get("cycles", 1_000_000_000)?)?;
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(136, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(136), solve_part_a(&example));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(64, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(64), solve_part_b(&example, 1_000_000_000));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(&["O.#\n.O.\n#.O\n"], allowed, |input| {
+            let _ = solve_part_a(input);
+            let _ = solve_part_b(input, 1_000_000_000);
+        });
     }
 }
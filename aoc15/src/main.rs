@@ -1,17 +1,30 @@
-use std::fs::read_to_string;
+use common::error::{overflow, parse_number, InputError};
+use common::input::read_input;
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
-    }
-    result
+fn allowed(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "=-,".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
 }
-fn parse_input(input: &Vec<String>) -> Vec<String> {
-    input[0]
+// Newlines are not part of the sequence, a wrapped paste is still one sequence.
+fn parse_input(input: &Vec<String>) -> Result<Vec<String>, InputError> {
+    if input.iter().all(|line| line.is_empty()) {
+        return Err(InputError::new("expected the initialization sequence"));
+    }
+    // HASH works on ASCII codes, control characters like a stray `\r` would
+    // silently change every hash
+    for (i, line) in input.iter().enumerate() {
+        if let Some(c) = line.chars().find(|c| !c.is_ascii() || c.is_ascii_control()) {
+            return Err(InputError::at(i, format!("unexpected character {:?}", c)));
+        }
+    }
+    Ok(input
+        .concat()
         .split(",")
         .map(|s| s.to_string())
-        .collect::<Vec<String>>()
+        .collect::<Vec<String>>())
 }
 
 fn hash(input: &String) -> u32 {
@@ -23,24 +36,24 @@ fn hash(input: &String) -> u32 {
     result
 }
 
-fn solve_part_a(input: &Vec<String>) -> u32 {
-    let steps = parse_input(input);
-    steps
+fn solve_part_a(input: &Vec<String>) -> Result<u32, InputError> {
+    let steps = parse_input(input)?;
+    Ok(steps
         .iter()
         .map(|k| hash(k))
         .collect::<Vec<u32>>()
         .iter()
-        .sum()
+        .sum())
 }
 
-fn solve_part_b(input: &Vec<String>) -> u32 {
-    let steps = parse_input(input);
+fn solve_part_b(input: &Vec<String>) -> Result<u32, InputError> {
+    let steps = parse_input(input)?;
     let mut boxes: Vec<Vec<(String, u32)>> = vec![Vec::new(); 256];
     for step in steps {
         match step.find("=") {
             Some(j) => {
                 let instr = step[..j].to_string();
-                let focal_length = step[j + 1..].parse::<u32>().unwrap();
+                let focal_length = parse_number::<u32>(&step[j + 1..], 0)?;
                 let index = hash(&instr) as usize;
                 let slot = &mut boxes[index];
                 match slot.iter().position(|(x, _)| *x == instr) {
@@ -49,7 +62,15 @@ fn solve_part_b(input: &Vec<String>) -> u32 {
                 }
             }
             None => {
-                let instr = step[..step.len() - 1].to_string();
+                let instr = match step.strip_suffix("-") {
+                    Some(instr) => instr.to_string(),
+                    None => {
+                        return Err(InputError::at(
+                            0,
+                            format!("step `{}` is neither `label=N` nor `label-`", step),
+                        ))
+                    }
+                };
                 let index = hash(&instr) as usize;
                 let slot = &mut boxes[index];
                 slot.retain(|(x, _)| *x != instr);
@@ -59,32 +80,45 @@ fn solve_part_b(input: &Vec<String>) -> u32 {
     let mut result = 0;
     for (i, b) in boxes.iter().enumerate() {
         for (j, (_, focal)) in b.iter().enumerate() {
-            result += (i + 1) as u32 * (j + 1) as u32 * focal
+            let power = ((i + 1) as u32 * (j + 1) as u32)
+                .checked_mul(*focal)
+                .ok_or_else(overflow)?;
+            result = power.checked_add(result).ok_or_else(overflow)?;
         }
     }
-    result
+    Ok(result)
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u32 = solve_part_a(&input);
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let result_part_a: u32 = solve_part_a(&input)?;
     println!("Part A result: {}", result_part_a);
-    let result_part_b: u32 = solve_part_b(&input);
+    let result_part_b: u32 = solve_part_b(&input)?;
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(1320, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(1320), solve_part_a(&example));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(145, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(145), solve_part_b(&example));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(&["ab=3,cd-,ab=7,ef=1,cd=2\n"], allowed, |input| {
+            let _ = solve_part_a(input);
+            let _ = solve_part_b(input);
+        });
     }
 }
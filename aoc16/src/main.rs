@@ -1,17 +1,34 @@
-use std::{collections::HashMap, collections::VecDeque, fs::read_to_string};
+use common::error::InputError;
+use common::input::read_input;
+use std::{collections::HashMap, collections::VecDeque};
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
-    }
-    result
+fn allowed(c: char) -> bool {
+    ".|-/\\".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
 }
-fn parse_input(input: &Vec<String>) -> Vec<Vec<String>> {
-    input
+fn parse_input(input: &Vec<String>) -> Result<Vec<Vec<String>>, InputError> {
+    let width = match input.first() {
+        Some(line) if !line.is_empty() => line.len(),
+        _ => return Err(InputError::new("the contraption is empty")),
+    };
+    for (i, line) in input.iter().enumerate() {
+        if let Some(c) = line.chars().find(|c| !"|-\\/.".contains(*c)) {
+            return Err(InputError::at(i, format!("unknown tile `{}`", c)));
+        }
+        if line.len() != width {
+            return Err(InputError::at(
+                i,
+                format!("expected {} tiles per row", width),
+            ));
+        }
+    }
+    Ok(input
         .iter()
         .map(|line| line.chars().map(|s| s.to_string()).collect::<Vec<String>>())
-        .collect::<Vec<Vec<String>>>()
+        .collect::<Vec<Vec<String>>>())
 }
 
 fn energize_beam_paths(
@@ -119,17 +136,17 @@ fn possible_starts(size_x: usize, size_y: usize) -> Vec<(usize, usize, usize)> {
     possible_starts
 }
 
-fn solve_part_a(input: &Vec<String>) -> u64 {
-    let map = parse_input(input);
+fn solve_part_a(input: &Vec<String>) -> Result<u64, InputError> {
+    let map = parse_input(input)?;
     let mut beams: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
     energize_beam_paths((0, 0, 0), &map, &mut beams);
-    beams.len() as u64
+    Ok(beams.len() as u64)
 }
 
-fn solve_part_b(input: &Vec<String>) -> u64 {
-    let map = parse_input(input);
+fn solve_part_b(input: &Vec<String>) -> Result<u64, InputError> {
+    let map = parse_input(input)?;
     let all_possible_starts: Vec<(usize, usize, usize)> = possible_starts(map.len(), map[0].len());
-    *all_possible_starts
+    Ok(*all_possible_starts
         .iter()
         .map(|starting_position| {
             let mut beams: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
@@ -139,29 +156,39 @@ fn solve_part_b(input: &Vec<String>) -> u64 {
         .collect::<Vec<u64>>()
         .iter()
         .max()
-        .unwrap()
+        .unwrap())
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input);
-    let result_part_b: u64 = solve_part_b(&input);
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let result_part_a: u64 = solve_part_a(&input)?;
+    let result_part_b: u64 = solve_part_b(&input)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(46, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(46), solve_part_a(&example));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(51, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(51), solve_part_b(&example));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(&[".|.\\\n-./.\n\\..|\n"], allowed, |input| {
+            let _ = solve_part_a(input);
+            let _ = solve_part_b(input);
+        });
     }
 }
@@ -1,21 +1,38 @@
-use std::{collections::HashMap, collections::HashSet, fs::read_to_string};
+use common::error::InputError;
+use common::input::read_input;
+use std::{collections::HashMap, collections::HashSet};
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
-    }
-    result
+fn allowed(c: char) -> bool {
+    c.is_ascii_digit()
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
 }
-fn parse_input(input: &Vec<String>) -> Vec<Vec<u64>> {
+fn parse_input(input: &Vec<String>) -> Result<Vec<Vec<u64>>, InputError> {
+    let width = match input.first() {
+        Some(line) if !line.is_empty() => line.len(),
+        _ => return Err(InputError::new("the map is empty")),
+    };
     input
         .iter()
-        .map(|line| {
+        .enumerate()
+        .map(|(i, line)| {
+            if line.len() != width {
+                return Err(InputError::at(
+                    i,
+                    format!("expected {} blocks per row", width),
+                ));
+            }
             line.chars()
-                .map(|s| s.to_string().parse::<u64>().unwrap())
-                .collect::<Vec<u64>>()
+                .map(|s| {
+                    s.to_digit(10).map(|d| d as u64).ok_or_else(|| {
+                        InputError::at(i, format!("heat loss `{}` is not a digit", s))
+                    })
+                })
+                .collect::<Result<Vec<u64>, InputError>>()
         })
-        .collect::<Vec<Vec<u64>>>()
+        .collect::<Result<Vec<Vec<u64>>, InputError>>()
 }
 #[derive(Eq, Hash, PartialEq, Debug, Clone, Copy)]
 struct Point {
@@ -89,7 +106,7 @@ fn generate_next_points_b(point: &Point, length_x: usize, length_y: usize) -> Ve
     points
 }
 
-fn find_shortest(map: &Vec<Vec<u64>>, part: &str) -> u64 {
+fn find_shortest(map: &Vec<Vec<u64>>, part: &str) -> Result<u64, InputError> {
     let mut unvisited: HashSet<Point> = HashSet::new();
     let mut visited_cost: HashMap<Point, u64> = HashMap::new();
     let mut visited: HashSet<Point> = HashSet::new();
@@ -104,27 +121,17 @@ fn find_shortest(map: &Vec<Vec<u64>>, part: &str) -> u64 {
     visited_cost.insert(starting_point.clone(), 0);
 
     loop {
-        let mut current: Point = Point {
-            x: 0,
-            y: 0,
-            direction: 0,
-            straight_length: 0,
-        };
-        let mut min = 100000;
-        for i in &unvisited {
-            let a = visited_cost.get(&i).unwrap();
-            if *a < min {
-                min = *a;
-                current = i.clone();
-            }
-        }
+        let current: Point = *unvisited
+            .iter()
+            .min_by_key(|i| visited_cost.get(i).unwrap())
+            .ok_or_else(|| InputError::new("the crucible cannot reach the factory"))?;
 
         unvisited.remove(&current);
 
         let current_cost = *visited_cost.get(&current).unwrap();
 
         if current.x == map.len() - 1 && current.y == map[0].len() - 1 {
-            return current_cost;
+            return Ok(current_cost);
         }
 
         let mut next_points = generate_next_points_a(&current, map.len(), map[0].len());
@@ -165,38 +172,48 @@ fn find_shortest(map: &Vec<Vec<u64>>, part: &str) -> u64 {
     }
 }
 
-fn solve_part_a(input: &Vec<String>) -> u64 {
-    let map = parse_input(input);
-    let a = find_shortest(&map, "A");
-    a
+fn solve_part_a(input: &Vec<String>) -> Result<u64, InputError> {
+    let map = parse_input(input)?;
+    let a = find_shortest(&map, "A")?;
+    Ok(a)
 }
 
-fn solve_part_b(input: &Vec<String>) -> u64 {
-    let map = parse_input(input);
-    let a = find_shortest(&map, "B");
-    a
+fn solve_part_b(input: &Vec<String>) -> Result<u64, InputError> {
+    let map = parse_input(input)?;
+    let a = find_shortest(&map, "B")?;
+    Ok(a)
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input);
-    let result_part_b: u64 = solve_part_b(&input);
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let result_part_a: u64 = solve_part_a(&input)?;
+    let result_part_b: u64 = solve_part_b(&input)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(102, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(102), solve_part_a(&example));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(94, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(94), solve_part_b(&example));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(&["24134\n32154\n32552\n34465\n45466\n"], allowed, |input| {
+            let _ = solve_part_a(input);
+            let _ = solve_part_b(input);
+        });
     }
 }
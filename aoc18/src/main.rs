@@ -1,109 +1,150 @@
-use std::fs::read_to_string;
+use common::error::{overflow, parse_number, InputError};
+use common::exact::Wide;
+use common::input::read_input;
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
+fn allowed(c: char) -> bool {
+    c.is_ascii_alphanumeric() || " ()#".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
+}
+fn split_line(index: usize, line: &str) -> Result<(&str, &str, &str), InputError> {
+    match line.split(" ").collect::<Vec<&str>>()[..] {
+        [direction, steps, color] => Ok((direction, steps, color)),
+        _ => Err(InputError::at(
+            index,
+            "expected `<direction> <steps> (#rrggbb)`",
+        )),
     }
-    result
 }
-fn parse_input_a(input: &Vec<String>) -> Vec<(String, u64)> {
+
+fn parse_input_a(input: &Vec<String>) -> Result<Vec<(String, u64)>, InputError> {
     input
         .iter()
-        .map(|line| {
-            let s = line.split(" ").collect::<Vec<&str>>();
-            (s[0].to_string(), s[1].to_string().parse::<u64>().unwrap())
+        .enumerate()
+        .map(|(i, line)| {
+            let (direction, steps, _) = split_line(i, line)?;
+            if !["R", "D", "L", "U"].contains(&direction) {
+                return Err(InputError::at(
+                    i,
+                    format!("unknown direction `{}`", direction),
+                ));
+            }
+            Ok((direction.to_string(), parse_number::<u64>(steps, i)?))
         })
-        .collect::<Vec<(String, u64)>>()
+        .collect::<Result<Vec<(String, u64)>, InputError>>()
 }
 
-fn parse_input_b(input: &Vec<String>) -> Vec<(String, u64)> {
+fn parse_input_b(input: &Vec<String>) -> Result<Vec<(String, u64)>, InputError> {
     input
         .iter()
-        .map(|line| {
-            let s = line.split(" ").collect::<Vec<&str>>();
-            let hex = s[2].replace("(#", "").replace(")", "");
+        .enumerate()
+        .map(|(i, line)| {
+            let (_, _, color) = split_line(i, line)?;
+            let hex = color
+                .strip_prefix("(#")
+                .and_then(|c| c.strip_suffix(")"))
+                .filter(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+                .ok_or_else(|| InputError::at(i, format!("malformed color `{}`", color)))?;
             let a = u64::from_str_radix(&hex[..5], 16).unwrap();
             let b = match &hex[5..] {
                 "0" => "R",
                 "1" => "D",
                 "2" => "L",
                 "3" => "U",
-                _ => unreachable!(),
+                d => return Err(InputError::at(i, format!("unknown direction code `{}`", d))),
             };
-            (b.to_string(), a)
+            Ok((b.to_string(), a))
         })
-        .collect::<Vec<(String, u64)>>()
+        .collect::<Result<Vec<(String, u64)>, InputError>>()
 }
 
-fn calculate_area(instructions: Vec<(String, u64)>) -> u64 {
-    let mut current_x: i64 = 0;
-    let mut current_y: i64 = 0;
-    let mut vertices: Vec<(i64, i64)> = Vec::new();
-    let mut counter = 0;
+// Coordinates are kept in Wide so the shoelace products of two u64 step
+// totals cannot wrap, anything still too large is reported as overflow.
+fn calculate_area(instructions: Vec<(String, u64)>) -> Result<u64, InputError> {
+    let mut current_x: Wide = 0;
+    let mut current_y: Wide = 0;
+    let mut vertices: Vec<(Wide, Wide)> = Vec::new();
+    let mut counter: Wide = 0;
     for (direction, step) in instructions {
-        counter += step;
+        let step = step as Wide;
+        counter = counter.checked_add(step).ok_or_else(overflow)?;
         match direction.as_str() {
-            "R" => {
-                current_y = current_y + step as i64;
-                vertices.push((current_x, current_y));
-            }
-            "L" => {
-                current_y = current_y - step as i64;
-                vertices.push((current_x, current_y));
-            }
-            "U" => {
-                current_x = current_x - step as i64;
-                vertices.push((current_x, current_y));
-            }
-            "D" => {
-                current_x = current_x + step as i64;
-                vertices.push((current_x, current_y));
-            }
-            _ => (),
+            "R" => current_y = current_y.checked_add(step).ok_or_else(overflow)?,
+            "L" => current_y = current_y.checked_sub(step).ok_or_else(overflow)?,
+            "U" => current_x = current_x.checked_sub(step).ok_or_else(overflow)?,
+            "D" => current_x = current_x.checked_add(step).ok_or_else(overflow)?,
+            _ => continue,
         }
+        vertices.push((current_x, current_y));
     }
-    let mut result: i64 = 0;
+    let mut result: Wide = 0;
     let length = vertices.len();
     for i in 0..length {
         let (x1, y1) = vertices[i];
         let (x2, y2) = vertices[(i + 1) % length];
-        result += (x1 * y2) - (y1 * x2);
+        result = x1
+            .checked_mul(y2)
+            .zip(y1.checked_mul(x2))
+            .and_then(|(a, b)| a.checked_sub(b))
+            .and_then(|cross| result.checked_add(cross))
+            .ok_or_else(overflow)?;
     }
 
-    (i64::abs(result) as u64 + counter) / 2 + 1
+    let area = result
+        .checked_abs()
+        .and_then(|r| r.checked_add(counter))
+        .ok_or_else(overflow)?
+        / 2
+        + 1;
+    u64::try_from(area).map_err(|_| overflow())
 }
 
-fn solve_part_a(input: &Vec<String>) -> u64 {
-    let instructions = parse_input_a(input);
+fn solve_part_a(input: &Vec<String>) -> Result<u64, InputError> {
+    let instructions = parse_input_a(input)?;
     calculate_area(instructions)
 }
 
-fn solve_part_b(input: &Vec<String>) -> u64 {
-    let instructions = parse_input_b(input);
+fn solve_part_b(input: &Vec<String>) -> Result<u64, InputError> {
+    let instructions = parse_input_b(input)?;
     calculate_area(instructions)
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input);
-    let result_part_b: u64 = solve_part_b(&input);
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let result_part_a: u64 = solve_part_a(&input)?;
+    let result_part_b: u64 = solve_part_b(&input)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(62, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(62), solve_part_a(&example));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(952408144115, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(952408144115), solve_part_b(&example));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(
+            &["R 4 (#000040)\nD 2 (#000021)\nL 4 (#000042)\nU 2 (#000023)\n"],
+            allowed,
+            |input| {
+                let _ = solve_part_a(input);
+                let _ = solve_part_b(input);
+            },
+        );
     }
 }
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, fs::read_to_string, ops::Range};
+use common::config::Config;
+use common::error::{overflow, parse_number, InputError};
+use common::input::read_input;
+use std::{collections::HashMap, ops::Range};
 
 struct Workflow {
     conditions: Vec<Condition>,
@@ -29,70 +32,102 @@ struct Rating {
     ratings: HashMap<Category, u64>,
 }
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
+fn allowed(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "{}<>:,=".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
+}
+fn parse_category(index: usize, c: Option<char>) -> Result<Category, InputError> {
+    match c {
+        Some('x') => Ok(Category::X),
+        Some('m') => Ok(Category::M),
+        Some('a') => Ok(Category::A),
+        Some('s') => Ok(Category::S),
+        _ => Err(InputError::at(
+            index,
+            "expected one of the categories x, m, a, s",
+        )),
+    }
+}
+
+fn parse_condition(index: usize, con: &str) -> Result<Condition, InputError> {
+    match con.split_once(":") {
+        Some((test, workflow)) => {
+            let mut chars = test.chars();
+            let category = parse_category(index, chars.next())?;
+            let operator = match chars.next() {
+                Some('>') => Operator::More,
+                Some('<') => Operator::Less,
+                _ => {
+                    return Err(InputError::at(
+                        index,
+                        format!("expected < or > in `{}`", con),
+                    ))
+                }
+            };
+            Ok(Condition {
+                category,
+                operator,
+                number: parse_number::<u64>(chars.as_str(), index)?,
+                workflow: workflow.to_string(),
+            })
+        }
+        None => Ok(Condition {
+            category: Category::None,
+            operator: Operator::None,
+            number: 0,
+            workflow: con.to_string(),
+        }),
     }
-    result
 }
-fn parse_input(input: &Vec<String>) -> (HashMap<String, Workflow>, Vec<Rating>) {
+
+fn parse_input(
+    input: &Vec<String>,
+) -> Result<(HashMap<String, Workflow>, Vec<Rating>), InputError> {
     let mut workflows: HashMap<String, Workflow> = HashMap::new();
     let mut ratings: Vec<Rating> = Vec::new();
     workflows.insert("A".to_string(), Workflow { conditions: vec![] });
     workflows.insert("R".to_string(), Workflow { conditions: vec![] });
     let mut flag = true;
-    for line in input {
-        if line == "" {
+    for (i, line) in input.iter().enumerate() {
+        if line.is_empty() {
             flag = false;
             continue;
         }
         if flag {
-            let a = line.find("{").unwrap();
-            let name = line[..a].to_string();
-            let mut conditions: Vec<Condition> = Vec::new();
-            let c = line[a + 1..line.len() - 1].to_string();
-            for con in c.split(",") {
-                match con.find(":") {
-                    Some(k) => conditions.push(Condition {
-                        category: match &con[..1] {
-                            "x" => Category::X,
-                            "m" => Category::M,
-                            "a" => Category::A,
-                            "s" => Category::S,
-                            _ => Category::None,
-                        },
-                        operator: match &con[1..2] {
-                            ">" => Operator::More,
-                            "<" => Operator::Less,
-                            _ => Operator::None,
-                        },
-                        number: con[2..k].parse::<u64>().unwrap(),
-                        workflow: con[k + 1..].to_string(),
-                    }),
-                    None => conditions.push(Condition {
-                        category: Category::None,
-                        operator: Operator::None,
-                        number: 0,
-                        workflow: con.to_string(),
-                    }),
-                }
-            }
+            let (name, c) = line
+                .strip_suffix("}")
+                .and_then(|l| l.split_once("{"))
+                .ok_or_else(|| InputError::at(i, "expected `name{rules}`"))?;
+            let conditions = c
+                .split(",")
+                .map(|con| parse_condition(i, con))
+                .collect::<Result<Vec<Condition>, InputError>>()?;
             let workflow = Workflow { conditions };
-            workflows.insert(name, workflow);
+            workflows.insert(name.to_string(), workflow);
         } else {
             let mut categories: HashMap<Category, u64> = HashMap::new();
-            for s in line[1..line.len() - 1].split(",") {
-                categories.insert(
-                    match &s[..1] {
-                        "x" => Category::X,
-                        "m" => Category::M,
-                        "a" => Category::A,
-                        "s" => Category::S,
-                        _ => Category::None,
-                    },
-                    s[2..].parse::<u64>().unwrap(),
-                );
+            let parts = line
+                .strip_prefix("{")
+                .and_then(|l| l.strip_suffix("}"))
+                .ok_or_else(|| InputError::at(i, "expected `{x=..,m=..,a=..,s=..}`"))?;
+            for s in parts.split(",") {
+                let (category, value) = s.split_once("=").ok_or_else(|| {
+                    InputError::at(i, format!("expected `category=rating`, found `{}`", s))
+                })?;
+                let category = match category.len() {
+                    1 => parse_category(i, category.chars().next())?,
+                    _ => parse_category(i, None)?,
+                };
+                categories.insert(category, parse_number::<u64>(value, i)?);
+            }
+            if categories.len() != 4 {
+                return Err(InputError::at(
+                    i,
+                    "every part needs a rating for x, m, a and s",
+                ));
             }
             ratings.push(Rating {
                 ratings: categories,
@@ -100,21 +135,51 @@ fn parse_input(input: &Vec<String>) -> (HashMap<String, Workflow>, Vec<Rating>)
         }
     }
 
-    (workflows, ratings)
+    if !workflows.contains_key("in") {
+        return Err(InputError::new("there is no `in` workflow"));
+    }
+    for workflow in workflows.values() {
+        for condition in &workflow.conditions {
+            if !workflows.contains_key(&condition.workflow) {
+                return Err(InputError::new(format!(
+                    "unknown workflow `{}`",
+                    condition.workflow
+                )));
+            }
+        }
+    }
+
+    Ok((workflows, ratings))
 }
 
+// `depth` counts the workflows followed so far, a chain longer than the number
+// of workflows can only mean they send parts around in a loop.
 fn calculate_configurations(
     workflows: &HashMap<String, Workflow>,
     name: String,
     mut ranges: HashMap<Category, Range<u64>>,
-) -> u64 {
-    let mut result = 0;
+    depth: usize,
+) -> Result<u64, InputError> {
+    let mut result: u64 = 0;
     if name == "A" {
         return ranges
             .values()
-            .fold(1, |acc, v| acc * (v.end - v.start + 1));
+            .try_fold(1u64, |acc, v| {
+                let count = match v.end.checked_sub(v.start) {
+                    Some(d) => d.checked_add(1)?,
+                    None => 0,
+                };
+                acc.checked_mul(count)
+            })
+            .ok_or_else(overflow);
     } else if name == "R" {
-        return 0;
+        return Ok(0);
+    }
+    if depth > workflows.len() {
+        return Err(InputError::new(format!(
+            "workflow `{}` is part of a loop",
+            name
+        )));
     }
 
     let current = workflows.get(&name).unwrap();
@@ -128,9 +193,17 @@ fn calculate_configurations(
 
                 if range.contains(&condition.number) {
                     *range = condition.number..max;
-                    ranges_new.insert(condition.category.clone(), min..condition.number - 1);
-                    result +=
-                        calculate_configurations(workflows, condition.workflow.clone(), ranges_new);
+                    // nothing is below the lowest rating
+                    if condition.number > min {
+                        ranges_new.insert(condition.category.clone(), min..condition.number - 1);
+                        let count = calculate_configurations(
+                            workflows,
+                            condition.workflow.clone(),
+                            ranges_new,
+                            depth + 1,
+                        )?;
+                        result = result.checked_add(count).ok_or_else(overflow)?;
+                    }
                 }
             }
             Operator::More => {
@@ -142,26 +215,44 @@ fn calculate_configurations(
                 if range.contains(&condition.number) {
                     *range = min..condition.number;
                     ranges_new.insert(condition.category.clone(), condition.number + 1..max);
-                    result +=
-                        calculate_configurations(workflows, condition.workflow.clone(), ranges_new);
+                    let count = calculate_configurations(
+                        workflows,
+                        condition.workflow.clone(),
+                        ranges_new,
+                        depth + 1,
+                    )?;
+                    result = result.checked_add(count).ok_or_else(overflow)?;
                 }
             }
             Operator::None => {
-                result +=
-                    calculate_configurations(workflows, condition.workflow.clone(), ranges.clone());
+                let count = calculate_configurations(
+                    workflows,
+                    condition.workflow.clone(),
+                    ranges.clone(),
+                    depth + 1,
+                )?;
+                result = result.checked_add(count).ok_or_else(overflow)?;
             }
         }
     }
 
-    result
+    Ok(result)
 }
-fn solve_part_a(input: &Vec<String>) -> u64 {
-    let (workflows, ratings) = parse_input(input);
+fn solve_part_a(input: &Vec<String>) -> Result<u64, InputError> {
+    let (workflows, ratings) = parse_input(input)?;
     let starting_name = "in";
-    let mut result = 0;
+    let mut result: u64 = 0;
     for rating in ratings {
         let mut current_name = starting_name;
+        let mut steps = 0;
         while current_name != "A" && current_name != "R" {
+            steps += 1;
+            if steps > workflows.len() {
+                return Err(InputError::new(format!(
+                    "workflow `{}` is part of a loop",
+                    current_name
+                )));
+            }
             let current = workflows.get(current_name).unwrap();
             for condition in &current.conditions {
                 match condition.operator {
@@ -184,43 +275,66 @@ fn solve_part_a(input: &Vec<String>) -> u64 {
             }
         }
         if current_name == "A" {
-            result += rating.ratings.values().sum::<u64>();
+            result = rating
+                .ratings
+                .values()
+                .try_fold(result, |sum, r| sum.checked_add(*r))
+                .ok_or_else(overflow)?;
         }
     }
 
-    result
+    Ok(result)
 }
 
-fn solve_part_b(input: &Vec<String>) -> u64 {
-    let (workflow, _) = parse_input(input);
+fn solve_part_b(input: &Vec<String>, min: u64, max: u64) -> Result<u64, InputError> {
+    let (workflow, _) = parse_input(input)?;
     let mut ranges: HashMap<Category, Range<u64>> = HashMap::new();
-    ranges.insert(Category::X, 1..4000);
-    ranges.insert(Category::M, 1..4000);
-    ranges.insert(Category::A, 1..4000);
-    ranges.insert(Category::S, 1..4000);
-    calculate_configurations(&workflow, "in".to_string(), ranges)
+    ranges.insert(Category::X, min..max);
+    ranges.insert(Category::M, min..max);
+    ranges.insert(Category::A, min..max);
+    ranges.insert(Category::S, min..max);
+    calculate_configurations(&workflow, "in".to_string(), ranges, 0)
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input);
-    let result_part_b: u64 = solve_part_b(&input);
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let config = Config::from_args("./config.toml", std::env::args().skip(1))?;
+    let result_part_a: u64 = solve_part_a(&input)?;
+    let result_part_b: u64 = solve_part_b(
+        &input,
+        config.get("ratings.min", 1)?,
+        config.get("ratings.max", 4000)?,
+    )?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(19114, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(19114), solve_part_a(&example));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(167409079868000, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(167409079868000), solve_part_b(&example, 1, 4000));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(
+            &["in{x<10:ab,m>5:A,R}\nab{s>2:R,A}\n\n{x=3,m=6,a=1,s=2}\n{x=12,m=1,a=9,s=9}\n"],
+            allowed,
+            |input| {
+                let _ = solve_part_a(input);
+                let _ = solve_part_b(input, 1, 4000);
+            },
+        );
     }
 }
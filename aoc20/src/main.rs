@@ -1,4 +1,6 @@
-use std::{collections::HashMap, fs::read_to_string};
+use common::error::{overflow, InputError};
+use common::input::read_input;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 #[derive(Debug, Clone)]
 struct Module {
@@ -24,42 +26,46 @@ enum State {
     On,
     Off,
 }
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
-    }
-    result
+fn allowed(c: char) -> bool {
+    c.is_ascii_alphanumeric() || " ->,%&".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
 }
 
-fn parse_input(input: &Vec<String>) -> HashMap<String, Module> {
+// A single button press that keeps sending pulses after this many is taken
+// to be a feedback loop that would never settle.
+const MAX_PULSES_PER_PRESS: u64 = 1_000_000;
+
+fn parse_input(input: &Vec<String>) -> Result<HashMap<String, Module>, InputError> {
     let mut modules: HashMap<String, Module> = HashMap::new();
 
-    for line in input {
-        let a: usize = line.find(" -").unwrap();
-        let mut name = line[..a].to_string();
-        let t: Type;
-        match name.as_str() {
-            "broadcaster" => t = Type::Broadcaster,
-            _ => match name.find("&") {
-                Some(_) => {
-                    name = name[1..].to_string();
-                    t = Type::Conjunction;
-                }
-                None => {
-                    name = name[1..].to_string();
-                    t = Type::FlipFlop;
-                }
+    for (i, line) in input.iter().enumerate() {
+        let (name, c) = line
+            .split_once(" -> ")
+            .ok_or_else(|| InputError::at(i, "expected `module -> destinations`"))?;
+        let (name, t) = match name {
+            "broadcaster" => (name, Type::Broadcaster),
+            _ => match (name.strip_prefix("&"), name.strip_prefix("%")) {
+                (Some(name), _) => (name, Type::Conjunction),
+                (_, Some(name)) => (name, Type::FlipFlop),
+                _ => return Err(InputError::at(i, format!("unknown module `{}`", name))),
             },
+        };
+        if name.is_empty() {
+            return Err(InputError::at(i, "module without a name"));
         }
-        let c = line[a + 3..].to_string();
         let mut connected: Vec<String> = Vec::new();
         let memory: HashMap<String, Pulse> = HashMap::new();
         for con in c.split(",") {
+            if con.trim().is_empty() {
+                return Err(InputError::at(i, "empty destination module"));
+            }
             connected.push(con.trim().to_string());
         }
         modules.insert(
-            name,
+            name.to_string(),
             Module {
                 t,
                 connected,
@@ -68,7 +74,10 @@ fn parse_input(input: &Vec<String>) -> HashMap<String, Module> {
             },
         );
     }
-    modules
+    if !modules.contains_key("broadcaster") {
+        return Err(InputError::new("there is no broadcaster module"));
+    }
+    Ok(modules)
 }
 
 // Populate the memory field of all the conjunctions. As the input is not ideal to do it while parsing.
@@ -77,13 +86,11 @@ fn build_memory(modules: &mut HashMap<String, Module>) {
     for (name, module) in modules.clone() {
         for i in &module.connected {
             match modules.get_mut(i) {
-                Some(m) => match m.t {
-                    Type::Conjunction => {
+                Some(m) => {
+                    if m.t == Type::Conjunction {
                         m.memory.insert(name.clone(), Pulse::Low);
                     }
-                    Type::FlipFlop => (),
-                    _ => unreachable!(),
-                },
+                }
                 None => {
                     modules.insert(
                         i.to_string(),
@@ -100,24 +107,28 @@ fn build_memory(modules: &mut HashMap<String, Module>) {
     }
 }
 
-fn send_pulse(modules: &mut HashMap<String, Module>) -> (u64, u64) {
+fn send_pulse(modules: &mut HashMap<String, Module>) -> Result<(u64, u64), InputError> {
     let mut counter: HashMap<Pulse, u64> = HashMap::new();
     counter.insert(Pulse::Low, 0);
     counter.insert(Pulse::High, 0);
 
     for _ in 0..1000 {
         *counter.entry(Pulse::Low).or_default() += 1;
-        let mut queue: Vec<(String, Pulse, String)> = Vec::new();
-        queue.push(("broadcaster".to_string(), Pulse::Low, "button".to_string()));
+        let mut queue: VecDeque<(String, Pulse, String)> = VecDeque::new();
+        queue.push_back(("broadcaster".to_string(), Pulse::Low, "button".to_string()));
 
-        while !queue.is_empty() {
-            let (module_name, input_pulse, prev_module) = queue.remove(0);
+        let mut pulses = 0;
+        while let Some((module_name, input_pulse, prev_module)) = queue.pop_front() {
+            pulses += 1;
+            if pulses > MAX_PULSES_PER_PRESS {
+                return Err(InputError::new("the modules keep pulsing forever"));
+            }
             let module = modules.get_mut(&module_name).unwrap();
             match module.t {
                 Type::Broadcaster => {
                     for i in &module.connected {
                         *counter.entry(Pulse::Low).or_default() += 1;
-                        queue.push((i.to_string(), Pulse::Low, module_name.clone()));
+                        queue.push_back((i.to_string(), Pulse::Low, module_name.clone()));
                     }
                 }
                 Type::Conjunction => {
@@ -130,7 +141,7 @@ fn send_pulse(modules: &mut HashMap<String, Module>) -> (u64, u64) {
                     }
                     for i in &module.connected {
                         *counter.entry(next_pulse.clone()).or_default() += 1;
-                        queue.push((i.to_string(), next_pulse.clone(), module_name.clone()));
+                        queue.push_back((i.to_string(), next_pulse.clone(), module_name.clone()));
                     }
                 }
                 Type::FlipFlop => match input_pulse {
@@ -150,7 +161,11 @@ fn send_pulse(modules: &mut HashMap<String, Module>) -> (u64, u64) {
 
                         for i in &module.connected {
                             *counter.entry(next_pulse.clone()).or_default() += 1;
-                            queue.push((i.to_string(), next_pulse.clone(), module_name.clone()));
+                            queue.push_back((
+                                i.to_string(),
+                                next_pulse.clone(),
+                                module_name.clone(),
+                            ));
                         }
                     }
                 },
@@ -159,21 +174,53 @@ fn send_pulse(modules: &mut HashMap<String, Module>) -> (u64, u64) {
         }
     }
 
-    (
+    Ok((
         *counter.get(&Pulse::Low).unwrap(),
         *counter.get(&Pulse::High).unwrap(),
-    )
+    ))
 }
 
-fn find_rx_low_cycle(modules: &mut HashMap<String, Module>) -> Vec<u64> {
+// rx is fed by a single conjunction, rx gets a low pulse once all of its inputs
+// have sent it a high pulse in the same press.
+fn find_rx_feeder(modules: &HashMap<String, Module>) -> Result<String, InputError> {
+    let feeders = modules
+        .iter()
+        .filter(|(_, m)| m.connected.iter().any(|c| c == "rx"))
+        .collect::<Vec<(&String, &Module)>>();
+    match feeders.as_slice() {
+        [(name, module)] if module.t == Type::Conjunction && !module.memory.is_empty() => {
+            Ok(name.to_string())
+        }
+        _ => Err(InputError::new(
+            "rx has to be fed by exactly one conjunction with inputs",
+        )),
+    }
+}
+
+// Flip-flop states and conjunction memories, identical keys mean the machine is
+// back where it was and the presses would repeat forever.
+fn machine_state(modules: &HashMap<String, Module>) -> Vec<bool> {
+    let mut state: Vec<bool> = Vec::new();
+    for module in modules.values() {
+        state.push(matches!(module.state, State::On));
+        state.extend(module.memory.values().map(|p| *p == Pulse::High));
+    }
+    state
+}
+
+fn find_rx_low_cycle(
+    modules: &mut HashMap<String, Module>,
+    feeder: &str,
+) -> Result<Vec<u64>, InputError> {
     let mut counter = 0;
     let mut prev = modules
-        .get("dn")
+        .get(feeder)
         .unwrap()
         .memory
         .iter()
         .map(|(name, _)| (name.clone(), 0))
         .collect::<HashMap<String, u64>>();
+    let mut seen: HashSet<Vec<bool>> = HashSet::new();
 
     loop {
         counter += 1;
@@ -182,15 +229,22 @@ fn find_rx_low_cycle(modules: &mut HashMap<String, Module>) -> Vec<u64> {
             // (which will result in a low rx)
             break;
         }
-        let mut queue: Vec<(String, Pulse, String)> = Vec::new();
-        queue.push(("broadcaster".to_string(), Pulse::Low, "button".to_string()));
-        while !queue.is_empty() {
-            let (module_name, input_pulse, prev_module) = queue.remove(0);
+        if !seen.insert(machine_state(modules)) {
+            return Err(InputError::new("rx never receives a low pulse"));
+        }
+        let mut queue: VecDeque<(String, Pulse, String)> = VecDeque::new();
+        queue.push_back(("broadcaster".to_string(), Pulse::Low, "button".to_string()));
+        let mut pulses = 0;
+        while let Some((module_name, input_pulse, prev_module)) = queue.pop_front() {
+            pulses += 1;
+            if pulses > MAX_PULSES_PER_PRESS {
+                return Err(InputError::new("the modules keep pulsing forever"));
+            }
             let module = modules.get_mut(&module_name.clone()).unwrap();
             match module.t {
                 Type::Broadcaster => {
                     for i in &module.connected {
-                        queue.push((i.to_string(), Pulse::Low, module_name.clone()));
+                        queue.push_back((i.to_string(), Pulse::Low, module_name.clone()));
                     }
                 }
                 Type::Conjunction => {
@@ -204,12 +258,12 @@ fn find_rx_low_cycle(modules: &mut HashMap<String, Module>) -> Vec<u64> {
                     for i in &module.connected {
                         // The first moment we encounter dn in the connected states we insert
                         // the cycle in our hashmap
-                        if i == "dn" && next_pulse == Pulse::High {
+                        if i == feeder && next_pulse == Pulse::High {
                             if *prev.get(&module_name).unwrap() == 0 {
                                 prev.insert(module_name.clone(), counter);
                             }
                         }
-                        queue.push((i.to_string(), next_pulse.clone(), module_name.clone()));
+                        queue.push_back((i.to_string(), next_pulse.clone(), module_name.clone()));
                     }
                 }
                 Type::FlipFlop => match input_pulse {
@@ -228,7 +282,11 @@ fn find_rx_low_cycle(modules: &mut HashMap<String, Module>) -> Vec<u64> {
                         }
 
                         for i in &module.connected {
-                            queue.push((i.to_string(), next_pulse.clone(), module_name.clone()));
+                            queue.push_back((
+                                i.to_string(),
+                                next_pulse.clone(),
+                                module_name.clone(),
+                            ));
                         }
                     }
                 },
@@ -236,7 +294,7 @@ fn find_rx_low_cycle(modules: &mut HashMap<String, Module>) -> Vec<u64> {
             }
         }
     }
-    prev.iter().map(|(_, c)| *c).collect::<Vec<u64>>()
+    Ok(prev.iter().map(|(_, c)| *c).collect::<Vec<u64>>())
 }
 
 fn gcd(mut a: u64, mut b: u64) -> u64 {
@@ -248,49 +306,62 @@ fn gcd(mut a: u64, mut b: u64) -> u64 {
     a
 }
 
-fn lcm(numbers: &[u64]) -> u64 {
-    let a = numbers[0];
-    let mut b = numbers[1];
-    if numbers.len() > 2 {
-        b = lcm(&numbers[1..]);
-    }
-    a * b / gcd(a, b)
+// None when the result does not fit in u64
+fn lcm(numbers: &[u64]) -> Option<u64> {
+    numbers
+        .iter()
+        .try_fold(1u64, |a, b| (a / gcd(a, *b)).checked_mul(*b))
 }
 
-fn solve_part_a(input: &Vec<String>) -> u64 {
-    let mut modules = parse_input(input);
+fn solve_part_a(input: &Vec<String>) -> Result<u64, InputError> {
+    let mut modules = parse_input(input)?;
     build_memory(&mut modules);
-    let (low, high) = send_pulse(&mut modules);
-    low * high
+    let (low, high) = send_pulse(&mut modules)?;
+    low.checked_mul(high).ok_or_else(overflow)
 }
 
-fn solve_part_b(input: &Vec<String>) -> u64 {
-    let mut modules = parse_input(input);
+fn solve_part_b(input: &Vec<String>) -> Result<u64, InputError> {
+    let mut modules = parse_input(input)?;
     build_memory(&mut modules);
-    let cycles = find_rx_low_cycle(&mut modules);
-    lcm(&cycles)
+    let feeder = find_rx_feeder(&modules)?;
+    let cycles = find_rx_low_cycle(&mut modules, &feeder)?;
+    lcm(&cycles).ok_or_else(overflow)
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input);
-    let result_part_b: u64 = solve_part_b(&input);
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let result_part_a: u64 = solve_part_a(&input)?;
+    let result_part_b: u64 = solve_part_b(&input)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example_1() {
-        let example: Vec<String> = read_lines("./inputs/example-a.txt");
-        assert_eq!(32000000, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example-a.txt").unwrap();
+        assert_eq!(Ok(32000000), solve_part_a(&example));
     }
 
     #[test]
     fn check_part_a_example_2() {
-        let example: Vec<String> = read_lines("./inputs/example-b.txt");
-        assert_eq!(11687500, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example-b.txt").unwrap();
+        assert_eq!(Ok(11687500), solve_part_a(&example));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(
+            &["broadcaster -> a, b\n%a -> x\n%b -> y\n&x -> c\n&y -> c\n&c -> rx\n"],
+            allowed,
+            |input| {
+                let _ = solve_part_a(input);
+                let _ = solve_part_b(input);
+            },
+        );
     }
 }
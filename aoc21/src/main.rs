@@ -1,17 +1,49 @@
-use std::{collections::HashMap, fs::read_to_string};
+use common::input::read_input;
+use common::{
+    config::Config,
+    error::{overflow, InputError},
+    exact::Rational,
+};
+use std::collections::HashMap;
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
-    }
-    result
+fn allowed(c: char) -> bool {
+    ".#S".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
 }
-fn parse_input(input: &Vec<String>) -> Vec<Vec<String>> {
-    input
+// Keeps a * x^2 of the part B quadratic well inside the range of Rational.
+const MAX_REPEATS: u64 = 1 << 40;
+
+fn parse_input(input: &Vec<String>) -> Result<Vec<Vec<String>>, InputError> {
+    let width = match input.first() {
+        Some(line) if !line.is_empty() => line.len(),
+        _ => return Err(InputError::new("the map is empty")),
+    };
+    for (i, line) in input.iter().enumerate() {
+        if let Some(c) = line.chars().find(|c| !".#S".contains(*c)) {
+            return Err(InputError::at(i, format!("unknown tile `{}`", c)));
+        }
+        if line.len() != width {
+            return Err(InputError::at(
+                i,
+                format!("expected {} tiles per row", width),
+            ));
+        }
+    }
+    if input
+        .iter()
+        .map(|line| line.matches("S").count())
+        .sum::<usize>()
+        != 1
+    {
+        return Err(InputError::new("expected exactly one S tile"));
+    }
+    Ok(input
         .iter()
         .map(|line| line.chars().map(|s| s.to_string()).collect::<Vec<String>>())
-        .collect::<Vec<Vec<String>>>()
+        .collect::<Vec<Vec<String>>>())
 }
 #[derive(Eq, Hash, PartialEq)]
 struct Point {
@@ -93,17 +125,20 @@ fn bfs(map: &Vec<Vec<String>>, total_steps: u64) -> u64 {
         .count() as u64
 }
 
-fn solve_part_a(input: &Vec<String>, total_steps: u64) -> u64 {
-    let map = parse_input(input);
-    bfs(&map, total_steps)
+fn solve_part_a(input: &Vec<String>, total_steps: u64) -> Result<u64, InputError> {
+    let map = parse_input(input)?;
+    Ok(bfs(&map, total_steps))
 }
 
-fn solve_part_b(input: &Vec<String>, total_steps: u64) -> u64 {
-    let map = parse_input(input);
+fn solve_part_b(input: &Vec<String>, total_steps: u64) -> Result<u64, InputError> {
+    let map = parse_input(input)?;
     let length = map.len() as u64;
-    let f_0 = bfs(&map, 65) as f64;
-    let f_1 = bfs(&map, 65 + length) as f64;
-    let f_2 = bfs(&map, 65 + length * 2) as f64;
+    if total_steps / length > MAX_REPEATS {
+        return Err(InputError::new("too many steps to extrapolate"));
+    }
+    let f_0 = Rational::from(bfs(&map, 65) as i64);
+    let f_1 = Rational::from(bfs(&map, 65 + length) as i64);
+    let f_2 = Rational::from(bfs(&map, 65 + length * 2) as i64);
 
     // f(x) = ax^2 + bx + c
     // f(0) = c
@@ -116,35 +151,50 @@ fn solve_part_b(input: &Vec<String>, total_steps: u64) -> u64 {
     // then from (1)
     // b = f(1) - f(2) - a
 
+    // a can be a half, but a quadratic through integer points stays an integer
+    // at every integer x, so the rational result below is always whole
+    let two = Rational::from(2i64);
     let c = f_0;
-    let a = (f_2 - (2.0 * f_1) + f_0) / 2.0;
+    let a = (f_2 - (two * f_1) + f_0) / two;
     let b = f_1 - f_0 - a;
 
-    let x = (total_steps / length) as f64;
+    let x = Rational::from((total_steps / length) as i64);
 
-    (a * x * x + b * x + c) as u64
+    let plots = (a * x * x + b * x + c).to_integer().unwrap();
+    u64::try_from(plots).map_err(|_| overflow())
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input, 64);
-    let result_part_b: u64 = solve_part_b(&input, 26501365);
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let config = Config::from_args("./config.toml", std::env::args().skip(1))?;
+    let result_part_a: u64 = solve_part_a(&input, config.get("part_a.steps", 64)?)?;
+    let result_part_b: u64 = solve_part_b(&input, config.get("part_b.steps", 26501365)?)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(16, solve_part_a(&example, 6));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(16), solve_part_a(&example, 6));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(16733044, solve_part_b(&example, 5000));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(16733044), solve_part_b(&example, 5000));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(&[".....\n.#.#.\n..S..\n.##..\n.....\n"], allowed, |input| {
+            let _ = solve_part_a(input, 64);
+            let _ = solve_part_b(input, 26501365);
+        });
     }
 }
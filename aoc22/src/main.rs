@@ -1,31 +1,48 @@
-use std::{collections::HashMap, fs::read_to_string, ops::Range};
+use common::error::{parse_number, InputError};
+use common::input::read_input;
+use std::{collections::HashMap, ops::Range};
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
+fn allowed(c: char) -> bool {
+    c.is_ascii_digit() || ",~".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
+}
+// Coordinates are read as u32 so that even a tower of every brick stacked on
+// top of each other still fits in usize.
+fn parse_corner(index: usize, corner: &str) -> Result<Vec<usize>, InputError> {
+    let coordinates = corner
+        .split(",")
+        .map(|s| parse_number::<u32>(s, index).map(|c| c as usize))
+        .collect::<Result<Vec<usize>, InputError>>()?;
+    if coordinates.len() != 3 {
+        return Err(InputError::at(
+            index,
+            format!("expected x,y,z, found `{}`", corner),
+        ));
     }
-    result
+    Ok(coordinates)
 }
-fn parse_input(input: &Vec<String>) -> Vec<Brick> {
+
+fn parse_input(input: &Vec<String>) -> Result<Vec<Brick>, InputError> {
     let mut bricks: Vec<Brick> = Vec::new();
-    for i in input {
-        let split = i.split("~").collect::<Vec<&str>>();
-        let start = split[0]
-            .split(",")
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect::<Vec<usize>>();
-        let end = split[1]
-            .split(",")
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect::<Vec<usize>>();
+    for (index, i) in input.iter().enumerate() {
+        let (start, end) = i
+            .split_once("~")
+            .ok_or_else(|| InputError::at(index, "expected `x,y,z~x,y,z`"))?;
+        let start = parse_corner(index, start)?;
+        let end = parse_corner(index, end)?;
+        if (0..3).any(|k| start[k] > end[k]) {
+            return Err(InputError::at(index, "a brick has to end after it starts"));
+        }
         bricks.push(Brick {
             x: start[0]..end[0] + 1,
             y: start[1]..end[1] + 1,
             z: start[2]..end[2] + 1,
         })
     }
-    bricks
+    Ok(bricks)
 }
 #[derive(Eq, Hash, PartialEq, Debug, Clone)]
 struct Brick {
@@ -34,7 +51,7 @@ struct Brick {
     z: Range<usize>,
 }
 fn collides(a: &Brick, b: &Brick) -> bool {
-    a.x.clone().any(|i| b.x.contains(&i)) && a.y.clone().any(|i| b.y.contains(&i))
+    a.x.start < b.x.end && b.x.start < a.x.end && a.y.start < b.y.end && b.y.start < a.y.end
 }
 
 fn simulation_a(bricks: &Vec<Brick>) -> (HashMap<Brick, Vec<Brick>>, HashMap<Brick, Vec<Brick>>) {
@@ -115,8 +132,8 @@ fn simulation_b(disintegrated: Option<&Brick>, bricks: &Vec<Brick>) -> HashMap<u
     stack
 }
 
-fn solve_part_a(input: &Vec<String>) -> u64 {
-    let mut bricks = parse_input(input);
+fn solve_part_a(input: &Vec<String>) -> Result<u64, InputError> {
+    let mut bricks = parse_input(input)?;
     bricks.sort_by_key(|c| c.z.start);
     let (below, above) = simulation_a(&bricks);
     let mut answer = 0;
@@ -134,11 +151,11 @@ fn solve_part_a(input: &Vec<String>) -> u64 {
             }
         }
     }
-    answer as u64
+    Ok(answer as u64)
 }
 
-fn solve_part_b(input: &Vec<String>) -> u64 {
-    let mut bricks = parse_input(input);
+fn solve_part_b(input: &Vec<String>) -> Result<u64, InputError> {
+    let mut bricks = parse_input(input)?;
     bricks.sort_by_key(|c| c.z.start);
     let stack: HashMap<u64, Brick> = simulation_b(None, &bricks);
     let mut answer = 0;
@@ -153,29 +170,43 @@ fn solve_part_b(input: &Vec<String>) -> u64 {
             }
         }
     }
-    answer
+    Ok(answer)
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input);
-    let result_part_b: u64 = solve_part_b(&input);
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let result_part_a: u64 = solve_part_a(&input)?;
+    let result_part_b: u64 = solve_part_b(&input)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(5, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(5), solve_part_a(&example));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(7, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(7), solve_part_b(&example));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(
+            &["0,0,1~2,0,1\n1,0,3~1,2,3\n0,2,5~0,2,6\n"],
+            allowed,
+            |input| {
+                let _ = solve_part_a(input);
+                let _ = solve_part_b(input);
+            },
+        );
     }
 }
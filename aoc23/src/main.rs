@@ -1,17 +1,41 @@
-use std::{collections::HashSet, fs::read_to_string};
+use common::error::InputError;
+use common::input::read_input;
+use std::collections::HashSet;
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
-    }
-    result
+fn allowed(c: char) -> bool {
+    "#.<>v".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
 }
-fn parse_input(input: &Vec<String>) -> Vec<Vec<String>> {
-    input
+// The hike starts in the second column of the top row and ends in the second to
+// last column of the bottom row.
+fn parse_input(input: &Vec<String>) -> Result<Vec<Vec<String>>, InputError> {
+    let width = input.first().map_or(0, |line| line.len());
+    if input.len() < 2 || width < 2 {
+        return Err(InputError::new(
+            "the map needs at least two rows and columns",
+        ));
+    }
+    for (i, line) in input.iter().enumerate() {
+        if let Some(c) = line.chars().find(|c| !"#.<>v".contains(*c)) {
+            return Err(InputError::at(i, format!("unknown tile `{}`", c)));
+        }
+        if line.len() != width {
+            return Err(InputError::at(
+                i,
+                format!("expected {} tiles per row", width),
+            ));
+        }
+    }
+    if &input[0][1..2] != "." || &input[input.len() - 1][width - 2..width - 1] != "." {
+        return Err(InputError::new("the start and the end have to be paths"));
+    }
+    Ok(input
         .iter()
         .map(|line| line.chars().map(|s| s.to_string()).collect::<Vec<String>>())
-        .collect::<Vec<Vec<String>>>()
+        .collect::<Vec<Vec<String>>>())
 }
 #[derive(Eq, Hash, PartialEq, Debug, Clone, Copy)]
 struct Point {
@@ -27,19 +51,19 @@ fn generate_next_points(
 ) -> Vec<Point> {
     let mut points: Vec<Point> = Vec::new();
     let steps: [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+    // a slope only leads on if it does not point off the map or into a wall
+    let slide = |x: usize, y: usize| {
+        if x < length_x && y < length_y && map[x][y] != "#" {
+            vec![Point { x, y }]
+        } else {
+            vec![]
+        }
+    };
     match map[point.x][point.y].as_str() {
-        ">" => points.push(Point {
-            x: point.x,
-            y: point.y + 1,
-        }),
-        "<" => points.push(Point {
-            x: point.x,
-            y: point.y - 1,
-        }),
-        "v" => points.push(Point {
-            x: point.x + 1,
-            y: point.y,
-        }),
+        ">" => points = slide(point.x, point.y + 1),
+        "<" if point.y > 0 => points = slide(point.x, point.y - 1),
+        "<" => (),
+        "v" => points = slide(point.x + 1, point.y),
         _ => {
             for dir in 0..4 {
                 let (step_x, step_y) = steps[dir];
@@ -111,22 +135,22 @@ fn find_longest_a(point: &Point, map: &Vec<Vec<String>>, mut visited: HashSet<Po
         .collect::<Vec<u64>>()
         .iter()
         .max()
-        .unwrap()
+        .unwrap_or(&0)
 }
 
 // simple dfs into our constructed graph with important vertices
 fn find_longest_b(
     point_index: usize,
     end_index: usize,
-    vertices: &[[(usize, u64); 4]; 40],
-    visited: &mut [bool; 40],
+    vertices: &Graph,
+    visited: &mut Vec<bool>,
     length: u64,
 ) -> u64 {
     if point_index == end_index {
         return length;
     }
     let mut max_value = 0;
-    for (k, v) in vertices[point_index] {
+    for &(k, v) in &vertices[point_index] {
         if v != 0 && !visited[point_index] {
             visited[point_index] = true;
             let result = find_longest_b(k, end_index, vertices, visited, length + v);
@@ -142,21 +166,22 @@ fn find_longest_b(
 fn populate_vertices(
     vertex_index: usize,
     map: &Vec<Vec<String>>,
-    vertices: &mut [(Point, [(Point, u64); 4]); 40],
+    vertices: &mut [(Point, Vec<(Point, u64)>)],
 ) {
     let mut visited: Vec<Point> = Vec::new();
     let mut next_steps: Vec<(Point, u64)> = Vec::new();
     next_steps.push((vertices[vertex_index].0, 0));
     let steps: [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
-    let mut neighbor_counter = 0;
     // we bfs into the maze till as we find other important vertice and we mark it with the length from our initial vertex
     while !next_steps.is_empty() {
         let (current, length) = next_steps.remove(0);
         visited.push(current.clone());
 
         if vertices.iter().any(|(e, _)| e == &current) && current != vertices[vertex_index].0 {
-            vertices[vertex_index].1[neighbor_counter] = (current, length);
-            neighbor_counter += 1;
+            // open areas can reach the same vertex more than once, keep the first
+            if !vertices[vertex_index].1.iter().any(|(p, _)| *p == current) {
+                vertices[vertex_index].1.push((current, length));
+            }
         } else {
             for dir in 0..4 {
                 let (step_x, step_y) = steps[dir];
@@ -187,14 +212,14 @@ fn populate_vertices(
     }
 }
 
-fn build_vertices(map: &Vec<Vec<String>>) -> (usize, [[(usize, u64); 4]; 40]) {
-    let mut vertices: [(Point, [(Point, u64); 4]); 40] =
-        [(Point { x: 0, y: 0 }, [(Point { x: 0, y: 0 }, 0); 4]); 40];
+// For every vertex, the indices of the vertices it leads to and how far away
+// they are.
+type Graph = Vec<Vec<(usize, u64)>>;
 
-    vertices[0] = (Point { x: 0, y: 1 }, [(Point { x: 0, y: 0 }, 0); 4]);
+fn build_vertices(map: &Vec<Vec<String>>) -> (usize, Graph) {
+    let mut vertices: Vec<(Point, Vec<(Point, u64)>)> = vec![(Point { x: 0, y: 1 }, vec![])];
 
     // find all vertices of importance, (ie where we split ways)
-    let mut index_counter: usize = 1;
     for x in 1..map.len() - 1 {
         for y in 1..map[0].len() - 1 {
             if map[x][y] == "#" {
@@ -213,78 +238,102 @@ fn build_vertices(map: &Vec<Vec<String>>) -> (usize, [[(usize, u64); 4]; 40]) {
                 }
             }
             if counter >= 3 {
-                vertices[index_counter] = (Point { x, y }, [(Point { x: 0, y: 0 }, 0); 4]);
-                index_counter += 1;
+                vertices.push((Point { x, y }, vec![]));
             }
         }
     }
-    vertices[index_counter] = (
+    let index_counter = vertices.len();
+    vertices.push((
         Point {
             x: map.len() - 1,
             y: map[0].len() - 2,
         },
-        [(Point { x: 0, y: 0 }, 0); 4],
-    );
+        vec![],
+    ));
 
     // for every vertex find connected vertices and their distance from them.
     for i in 0..=index_counter {
         populate_vertices(i, map, &mut vertices);
     }
     // each index should be pointing to another index with a distance
-    let mut new_vertices = [[(0, 0); 4]; 40];
-    for i in 0..=index_counter {
-        let (_, list) = vertices[i];
-        for (index, (point, length)) in list.iter().filter(|&(_, v)| *v != 0).enumerate() {
-            new_vertices[i][index] = (
-                vertices.iter().position(|(p, _)| p == point).unwrap(),
-                *length,
-            );
-        }
-    }
+    let new_vertices = vertices
+        .iter()
+        .map(|(_, list)| {
+            list.iter()
+                .filter(|&(_, v)| *v != 0)
+                .map(|(point, length)| {
+                    (
+                        vertices.iter().position(|(p, _)| p == point).unwrap(),
+                        *length,
+                    )
+                })
+                .collect::<Vec<(usize, u64)>>()
+        })
+        .collect::<Graph>();
 
     (index_counter, new_vertices)
 }
 
-fn solve_part_a(input: &Vec<String>) -> u64 {
-    let map = parse_input(input);
-    find_longest_a(&Point { x: 0, y: 1 }, &map, HashSet::new())
+fn no_path() -> InputError {
+    InputError::new("there is no path from the start to the end")
 }
 
-fn solve_part_b(input: &Vec<String>) -> u64 {
-    let map = parse_input(input);
+fn solve_part_a(input: &Vec<String>) -> Result<u64, InputError> {
+    let map = parse_input(input)?;
+    match find_longest_a(&Point { x: 0, y: 1 }, &map, HashSet::new()) {
+        0 => Err(no_path()),
+        longest => Ok(longest),
+    }
+}
+
+fn solve_part_b(input: &Vec<String>) -> Result<u64, InputError> {
+    let map = parse_input(input)?;
     let (end_index, vertices) = build_vertices(&map);
-    let (end_index, end_length) = vertices[end_index][0];
-    let (start_index, start_length) = vertices[0][0];
+    let (end_index, end_length) = *vertices[end_index].first().ok_or_else(no_path)?;
+    let (start_index, start_length) = *vertices[0].first().ok_or_else(no_path)?;
 
-    find_longest_b(
+    match find_longest_b(
         start_index,
         end_index,
         &vertices,
-        &mut [false; 40],
+        &mut vec![false; vertices.len()],
         end_length + start_length,
-    )
+    ) {
+        0 => Err(no_path()),
+        longest => Ok(longest),
+    }
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input);
-    let result_part_b: u64 = solve_part_b(&input);
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let result_part_a: u64 = solve_part_a(&input)?;
+    let result_part_b: u64 = solve_part_b(&input)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B result: {}", result_part_b);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(94, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(94), solve_part_a(&example));
     }
 
     #[test]
     fn check_part_b_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(154, solve_part_b(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(154), solve_part_b(&example));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(&["#.###\n#...#\n#.#v#\n#...#\n###.#\n"], allowed, |input| {
+            let _ = solve_part_a(input);
+            let _ = solve_part_b(input);
+        });
     }
 }
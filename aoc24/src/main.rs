@@ -1,77 +1,115 @@
-use std::fs::read_to_string;
+use common::input::read_input;
+use common::{
+    config::Config,
+    error::{parse_number, InputError},
+    exact::{Rational, Wide},
+};
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
-    }
-    result
+fn allowed(c: char) -> bool {
+    c.is_ascii_digit() || " ,@-".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
 }
 #[derive(Debug, Clone)]
 struct Point {
-    x: f64,
-    y: f64,
+    x: i64,
+    y: i64,
 }
 #[derive(Debug, Clone)]
 struct Velocity {
-    v_x: f64,
-    v_y: f64,
+    v_x: i64,
+    v_y: i64,
+}
+
+// Bounds that keep every intermediate product of the exact crossing point
+// inside i128, with room to spare for the puzzle's ~4e14 positions.
+const MAX_POSITION: i64 = 1 << 50;
+const MAX_VELOCITY: i64 = 1 << 20;
+
+fn parse_triple(index: usize, text: &str, limit: i64) -> Result<Vec<i64>, InputError> {
+    let values = text
+        .split(",")
+        .map(|v| parse_number::<i64>(v, index))
+        .collect::<Result<Vec<i64>, InputError>>()?;
+    if values.len() != 3 {
+        return Err(InputError::at(
+            index,
+            format!("expected x, y, z in `{}`", text.trim()),
+        ));
+    }
+    if values.iter().any(|v| v.unsigned_abs() > limit as u64) {
+        return Err(InputError::at(
+            index,
+            format!("values must be within ±{}", limit),
+        ));
+    }
+    Ok(values)
 }
 
-fn parse_input(input: &Vec<String>) -> Vec<(Point, Velocity)> {
+fn parse_input(input: &Vec<String>) -> Result<Vec<(Point, Velocity)>, InputError> {
     input
         .iter()
-        .map(|line| {
-            let mut a = line.split("@");
-            let mut point = a.next().unwrap().split(",");
-            let x = point.next().unwrap().trim().parse::<f64>().unwrap();
-            let y = point.next().unwrap().trim().parse::<f64>().unwrap();
-            let mut velocities = a.next().unwrap().split(",");
-            let v_x = velocities.next().unwrap().trim().parse::<f64>().unwrap();
-            let v_y = velocities.next().unwrap().trim().parse::<f64>().unwrap();
-            (Point { x, y }, Velocity { v_x, v_y })
+        .enumerate()
+        .map(|(i, line)| {
+            let (point, velocities) = line
+                .split_once("@")
+                .ok_or_else(|| InputError::at(i, "expected `position @ velocity`"))?;
+            let point = parse_triple(i, point, MAX_POSITION)?;
+            let velocities = parse_triple(i, velocities, MAX_VELOCITY)?;
+            Ok((
+                Point {
+                    x: point[0],
+                    y: point[1],
+                },
+                Velocity {
+                    v_x: velocities[0],
+                    v_y: velocities[1],
+                },
+            ))
         })
-        .collect::<Vec<(Point, Velocity)>>()
+        .collect::<Result<Vec<(Point, Velocity)>, InputError>>()
 }
 
-fn build_linear_function(point: &(Point, Velocity)) -> (f64, f64) {
-    let y1 = point.0.y;
-    let x1 = point.0.x;
-    let y2 = y1 + point.1.v_y;
-    let x2 = x1 + point.1.v_x;
-    let a = (y2 - y1) / (x2 - x1);
-    let b = -1.0 * a * x1 + y1;
-
-    (a, b)
+// Solve p1 + t * v1 = p2 + s * v2 for the times t and s at which each hailstone
+// reaches the crossing point. Coordinates are around 4e14 so a float slope loses
+// digits; the cross products below fit comfortably in i128.
+fn crossing_times(
+    one: &(Point, Velocity),
+    two: &(Point, Velocity),
+) -> Option<(Rational, Rational)> {
+    let (p1, v1) = one;
+    let (p2, v2) = two;
+    let det = v1.v_x as Wide * v2.v_y as Wide - v1.v_y as Wide * v2.v_x as Wide;
+    if det == 0 {
+        // parallel paths never cross
+        return None;
+    }
+    let dx = p2.x as Wide - p1.x as Wide;
+    let dy = p2.y as Wide - p1.y as Wide;
+    let t = Rational::new(dx * v2.v_y as Wide - dy * v2.v_x as Wide, det);
+    let s = Rational::new(dx * v1.v_y as Wide - dy * v1.v_x as Wide, det);
+    Some((t, s))
 }
 
-fn collides(one: &(Point, Velocity), two: &(Point, Velocity), low: f64, high: f64) -> bool {
-    let (a, b) = build_linear_function(one);
-    let (d, c) = build_linear_function(two);
-
-    let x = (b - c) / (d - a);
-    let y = (b - c) / (d - a) * a + b;
-
-    if x > low && x < high && y > low && y < high {
-        // check for times before t=0;
-        if one.1.v_x > 0.0 && one.0.x > x || one.1.v_y > 0.0 && one.0.y > y {
-            false
-        } else if two.1.v_x > 0.0 && two.0.x >= x || two.1.v_y > 0.0 && two.0.y >= y {
-            false
-        } else if one.1.v_x < 0.0 && one.0.x <= x || one.1.v_y < 0.0 && one.0.y <= y {
-            false
-        } else if two.1.v_x < 0.0 && two.0.x <= x || two.1.v_y < 0.0 && two.0.y <= y {
-            false
-        } else {
-            true
-        }
-    } else {
-        false
+fn collides(one: &(Point, Velocity), two: &(Point, Velocity), low: i64, high: i64) -> bool {
+    let (t, s) = match crossing_times(one, two) {
+        Some(times) => times,
+        None => return false,
+    };
+    // check for times before t=0;
+    if t <= Rational::from(0i64) || s <= Rational::from(0i64) {
+        return false;
     }
+    let x = Rational::from(one.0.x) + Rational::from(one.1.v_x) * t;
+    let y = Rational::from(one.0.y) + Rational::from(one.1.v_y) * t;
+    let (low, high) = (Rational::from(low), Rational::from(high));
+    x > low && x < high && y > low && y < high
 }
 
-fn solve_part_a(input: &Vec<String>, low: f64, high: f64) -> u64 {
-    let rocks = parse_input(input);
+fn solve_part_a(input: &Vec<String>, low: i64, high: i64) -> Result<u64, InputError> {
+    let rocks = parse_input(input)?;
     let mut counter = 0;
     for i in 0..rocks.len() {
         for j in i + 1..rocks.len() {
@@ -80,21 +118,40 @@ fn solve_part_a(input: &Vec<String>, low: f64, high: f64) -> u64 {
             }
         }
     }
-    counter
+    Ok(counter)
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input, 200000000000000.0, 400000000000000.0);
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let config = Config::from_args("./config.toml", std::env::args().skip(1))?;
+    let result_part_a: u64 = solve_part_a(
+        &input,
+        config.get("window.low", 200000000000000)?,
+        config.get("window.high", 400000000000000)?,
+    )?;
     println!("Part A result: {}", result_part_a);
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(2, solve_part_a(&example, 7.0, 27.0));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(2), solve_part_a(&example, 7, 27));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(
+            &["19, 13, 30 @ -2, 1, -2\n18, 19, 22 @ -1, -1, -2\n"],
+            allowed,
+            |input| {
+                let _ = solve_part_a(input, 7, 27);
+                let _ = solve_part_a(input, i64::MIN, i64::MAX);
+            },
+        );
     }
 }
@@ -1,27 +1,39 @@
+use common::error::InputError;
+use common::input::read_input;
 use rand::prelude::*;
-use std::{collections::HashMap, fs::read_to_string};
+use std::collections::HashMap;
 
-fn read_lines(filename: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    for line in read_to_string(filename).unwrap().lines() {
-        result.push(line.to_string())
-    }
-    result
+fn allowed(c: char) -> bool {
+    c.is_ascii_alphabetic() || " :".contains(c)
+}
+
+fn read_lines(filename: &str) -> Result<Vec<String>, InputError> {
+    read_input(filename, allowed)
 }
-fn parse_input(input: &Vec<String>) -> HashMap<String, Vec<String>> {
+fn parse_input(input: &Vec<String>) -> Result<HashMap<String, Vec<String>>, InputError> {
     let mut graph: HashMap<String, Vec<String>> = HashMap::new();
-    input.iter().for_each(|line| {
-        let mut iter = line.split(":");
-        let node = iter.next().unwrap();
-        let mut next_nodes = iter
-            .next()
-            .unwrap()
-            .split(" ")
-            .map(|node| node.trim().to_string())
+    for (i, line) in input.iter().enumerate() {
+        let (node, next_nodes) = line
+            .split_once(":")
+            .ok_or_else(|| InputError::at(i, "expected `component: connected components`"))?;
+        // merged nodes are named by joining with spaces, so names cannot have any
+        if node.is_empty() || node.contains(char::is_whitespace) {
+            return Err(InputError::at(
+                i,
+                format!("invalid component name `{}`", node),
+            ));
+        }
+        // a wire from a component to itself never crosses a cut
+        let next_nodes = next_nodes
+            .split_whitespace()
+            .filter(|next| *next != node)
+            .map(|node| node.to_string())
             .collect::<Vec<String>>();
-        next_nodes = next_nodes[1..].to_vec();
-        graph.insert(node.to_string(), next_nodes.clone());
-    });
+        graph
+            .entry(node.to_string())
+            .or_default()
+            .extend(next_nodes);
+    }
     for (node, next_nodes) in &graph.clone() {
         for next_node in next_nodes {
             graph
@@ -34,12 +46,24 @@ fn parse_input(input: &Vec<String>) -> HashMap<String, Vec<String>> {
                 .or_insert(vec![node.to_string()]);
         }
     }
-    graph
+    if graph.len() < 2 {
+        return Err(InputError::new("expected at least two components"));
+    }
+    if let Some((node, _)) = graph.iter().find(|(_, next_nodes)| next_nodes.is_empty()) {
+        return Err(InputError::new(format!(
+            "component `{}` is not connected",
+            node
+        )));
+    }
+    Ok(graph)
 }
 
-fn karger(graph: HashMap<String, Vec<String>>) -> u64 {
+// A single run finds a given minimum cut with probability at least 2 / n^2, so
+// after n^2 runs it is only missed with probability below e^-2. In practice the
+// three wires are found within a few runs.
+fn karger(graph: HashMap<String, Vec<String>>) -> Result<u64, InputError> {
     let mut rng = rand::thread_rng();
-    loop {
+    for _ in 0..graph.len() * graph.len() {
         // run iterations till we get a solution
         let mut temp_graph = graph.clone();
         // coalesce nodes till there are 2 left
@@ -48,6 +72,10 @@ fn karger(graph: HashMap<String, Vec<String>>) -> u64 {
             let random_vertex = rng.gen_range(0..temp_graph.len());
             let old_v_1 = temp_graph.keys().nth(random_vertex).unwrap().clone();
             let mut neighbors = temp_graph.remove(&old_v_1).unwrap();
+            if neighbors.is_empty() {
+                // a whole part of the graph has merged into one node
+                return Err(InputError::new("the components are not all connected"));
+            }
 
             // get a random neighbor of that vertex and remove it from the graph
             let random_neighbor = rng.gen_range(0..neighbors.len());
@@ -79,32 +107,46 @@ fn karger(graph: HashMap<String, Vec<String>>) -> u64 {
         // if our graph has exactly three bridges we stop
         // and by our naming convetion we also know the number of each graph
         if temp_graph.values().last().unwrap().len() == 3 {
-            return temp_graph
+            return Ok(temp_graph
                 .iter()
                 .map(|(vertex, _)| vertex.split(" ").collect::<Vec<&str>>().len() as u64)
-                .product::<u64>();
+                .product::<u64>());
         }
     }
+    Err(InputError::new("could not find three wires to cut"))
 }
 
-fn solve_part_a(input: &Vec<String>) -> u64 {
-    let graph = parse_input(input);
+fn solve_part_a(input: &Vec<String>) -> Result<u64, InputError> {
+    let graph = parse_input(input)?;
     karger(graph)
 }
 
-fn main() {
-    let input: Vec<String> = read_lines("./inputs/input.txt");
-    let result_part_a: u64 = solve_part_a(&input);
+fn main() -> Result<(), InputError> {
+    let input: Vec<String> = read_lines("./inputs/input.txt")?;
+    let result_part_a: u64 = solve_part_a(&input)?;
     println!("Part A result: {}", result_part_a);
     println!("Part B -> Press the button");
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::fuzz::fuzz_lines;
     #[test]
     fn check_part_a_example() {
-        let example: Vec<String> = read_lines("./inputs/example.txt");
-        assert_eq!(54, solve_part_a(&example));
+        let example: Vec<String> = read_lines("./inputs/example.txt").unwrap();
+        assert_eq!(Ok(54), solve_part_a(&example));
+    }
+
+    #[test]
+    fn fuzz_parse_and_solve() {
+        fuzz_lines(
+            &["a: b c d\nb: c d\nc: d e\nd: f\ne: f g h\nf: g h\ng: h\n"],
+            allowed,
+            |input| {
+                let _ = solve_part_a(input);
+            },
+        );
     }
 }
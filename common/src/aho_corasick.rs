@@ -0,0 +1,120 @@
+// Multi-pattern search over bytes (Aho–Corasick). The patterns are compiled
+// once into a trie with failure links, after which a single left to right pass
+// over the text reports every occurrence of every pattern, overlapping ones
+// included, in time linear in the text plus the number of matches.
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    /// Index of the pattern in the list the automaton was built from.
+    pub pattern: usize,
+    /// Byte range of the occurrence in the text.
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct AhoCorasick {
+    // per state: byte -> next state, state 0 is the root
+    next: Vec<HashMap<u8, usize>>,
+    fail: Vec<usize>,
+    // patterns ending in a state, including those reached over failure links
+    output: Vec<Vec<usize>>,
+    lengths: Vec<usize>,
+}
+
+impl AhoCorasick {
+    /// Empty patterns are accepted but never match.
+    pub fn new<P: AsRef<[u8]>>(patterns: &[P]) -> AhoCorasick {
+        let mut automaton = AhoCorasick {
+            next: vec![HashMap::new()],
+            fail: vec![0],
+            output: vec![Vec::new()],
+            lengths: patterns.iter().map(|p| p.as_ref().len()).collect(),
+        };
+        for (index, pattern) in patterns.iter().enumerate() {
+            let pattern = pattern.as_ref();
+            if pattern.is_empty() {
+                continue;
+            }
+            let mut state = 0;
+            for byte in pattern {
+                state = match automaton.next[state].get(byte) {
+                    Some(next) => *next,
+                    None => {
+                        automaton.next.push(HashMap::new());
+                        automaton.fail.push(0);
+                        automaton.output.push(Vec::new());
+                        let created = automaton.next.len() - 1;
+                        automaton.next[state].insert(*byte, created);
+                        created
+                    }
+                };
+            }
+            automaton.output[state].push(index);
+        }
+
+        // breadth first, so the failure target of a state is always finished
+        // before the state itself
+        let mut queue: VecDeque<usize> = automaton.next[0].values().copied().collect();
+        while let Some(state) = queue.pop_front() {
+            let children: Vec<(u8, usize)> = automaton.next[state]
+                .iter()
+                .map(|(b, s)| (*b, *s))
+                .collect();
+            for (byte, child) in children {
+                let target = automaton.step(automaton.fail[state], byte);
+                automaton.fail[child] = target;
+                let inherited = automaton.output[target].clone();
+                automaton.output[child].extend(inherited);
+                queue.push_back(child);
+            }
+        }
+        automaton
+    }
+
+    fn step(&self, mut state: usize, byte: u8) -> usize {
+        loop {
+            if let Some(next) = self.next[state].get(&byte) {
+                return *next;
+            }
+            if state == 0 {
+                return 0;
+            }
+            state = self.fail[state];
+        }
+    }
+
+    /// Every occurrence of every pattern, ordered by where it ends (longer
+    /// patterns first when several end at the same byte).
+    pub fn find_overlapping(&self, text: impl AsRef<[u8]>) -> Vec<Match> {
+        let mut matches: Vec<Match> = Vec::new();
+        let mut state = 0;
+        for (i, byte) in text.as_ref().iter().enumerate() {
+            state = self.step(state, *byte);
+            let end = i + 1;
+            matches.extend(self.output[state].iter().map(|pattern| Match {
+                pattern: *pattern,
+                start: end - self.lengths[*pattern],
+                end,
+            }));
+        }
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn check_overlapping_matches() {
+        let automaton = AhoCorasick::new(&["he", "she", "his", "hers", ""]);
+        let found = automaton
+            .find_overlapping("ushers")
+            .iter()
+            .map(|m| (m.pattern, m.start, m.end))
+            .collect::<Vec<(usize, usize, usize)>>();
+        assert_eq!(vec![(1, 1, 4), (0, 2, 4), (3, 2, 6)], found);
+        assert!(automaton.find_overlapping("").is_empty());
+    }
+}
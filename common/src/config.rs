@@ -0,0 +1,204 @@
+// Per-day puzzle parameters. Each day keeps its defaults in code and reads
+// overrides from a small TOML file (only `key = value` pairs, `[section]`
+// headers and `#` comments) and from the command line:
+//
+//     cargo run -- --config other.toml --set part_b.steps=5000
+//
+// Keys inside a section are addressed as `section.key`.
+use crate::error::InputError;
+use std::{collections::HashMap, fmt, fs::read_to_string, str::FromStr};
+
+#[derive(Debug, PartialEq)]
+pub struct ConfigError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Config {
+    values: HashMap<String, String>,
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+        let mut section = String::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[') {
+                match name.strip_suffix(']') {
+                    Some(name) if !name.trim().is_empty() => {
+                        section = name.trim().to_string() + "."
+                    }
+                    _ => {
+                        return Err(ConfigError {
+                            line: i + 1,
+                            message: format!("malformed section header `{}`", line),
+                        })
+                    }
+                }
+                continue;
+            }
+            match line.split_once('=') {
+                Some((key, value)) if !key.trim().is_empty() => {
+                    config.set(&(section.clone() + key.trim()), value.trim());
+                }
+                _ => {
+                    return Err(ConfigError {
+                        line: i + 1,
+                        message: format!("expected `key = value`, found `{}`", line),
+                    })
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    // A missing file is not an error, the defaults in code are used instead.
+    pub fn load(path: &str) -> Result<Config, InputError> {
+        match read_to_string(path) {
+            Ok(text) => {
+                Config::parse(&text).map_err(|e| InputError::new(format!("{}: {}", path, e)))
+            }
+            Err(_) => Ok(Config::default()),
+        }
+    }
+
+    // Loads `default_path` (or the file given with `--config`) and applies every
+    // `--set key=value` on top. Arguments this does not know are left alone so
+    // days can have their own flags next to these.
+    pub fn from_args(
+        default_path: &str,
+        args: impl Iterator<Item = String>,
+    ) -> Result<Config, InputError> {
+        let args = args.collect::<Vec<String>>();
+        let mut path = default_path.to_string();
+        let mut overrides: Vec<String> = Vec::new();
+        let mut i = 0;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--config" if i + 1 < args.len() => {
+                    path = args[i + 1].clone();
+                    i += 1;
+                }
+                "--set" if i + 1 < args.len() => {
+                    overrides.push(args[i + 1].clone());
+                    i += 1;
+                }
+                _ => (),
+            }
+            i += 1;
+        }
+        let mut config = Config::load(&path)?;
+        for o in overrides {
+            match o.split_once('=') {
+                Some((key, value)) => config.set(key.trim(), value.trim()),
+                None => {
+                    return Err(InputError::new(format!(
+                        "--set expects key=value, found `{}`",
+                        o
+                    )))
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) {
+        self.values.insert(key.to_string(), value.to_string());
+    }
+
+    pub fn get<T: FromStr>(&self, key: &str, default: T) -> Result<T, InputError> {
+        match self.values.get(key) {
+            Some(raw) => parse_typed(key, raw),
+            None => Ok(default),
+        }
+    }
+
+    /// Every `key = value` directly inside `[name]`, sorted by key. For tables
+    /// whose keys are data, like the colors in a bag.
+    pub fn section<T: FromStr>(&self, name: &str) -> Result<Vec<(String, T)>, InputError> {
+        let prefix = format!("{}.", name);
+        let mut entries = self
+            .values
+            .iter()
+            .filter_map(|(key, raw)| Some((key, key.strip_prefix(&prefix)?, raw)))
+            .filter(|(_, key, _)| !key.contains('.'))
+            .map(|(full, key, raw)| Ok((key.to_string(), parse_typed(full, raw)?)))
+            .collect::<Result<Vec<(String, T)>, InputError>>()?;
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(entries)
+    }
+}
+
+fn parse_typed<T: FromStr>(key: &str, raw: &str) -> Result<T, InputError> {
+    parse_value(raw)
+        .parse::<T>()
+        .map_err(|_| InputError::new(format!("invalid value for `{}`: {}", key, raw)))
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => (),
+        }
+    }
+    line
+}
+
+// Quoted values are taken verbatim, anything else is a number or a bool where
+// TOML allows `_` as a digit separator.
+fn parse_value(raw: &str) -> String {
+    match raw.strip_prefix('"').and_then(|r| r.strip_suffix('"')) {
+        Some(s) => s.to_string(),
+        None => raw.replace('_', ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn check_sections_and_overrides() {
+        let text = "# cycles to run\ncycles = 1_000_000_000\n\n[part_b]\nsteps = 26501365 # comment\nname = \"a # b\"\n";
+        let mut config = Config::parse(text).unwrap();
+        assert_eq!(1_000_000_000, config.get::<u64>("cycles", 0).unwrap());
+        assert_eq!(26501365, config.get::<u64>("part_b.steps", 0).unwrap());
+        assert_eq!(
+            "a # b",
+            config.get::<String>("part_b.name", String::new()).unwrap()
+        );
+        assert_eq!(64, config.get::<u64>("part_a.steps", 64).unwrap());
+        config.set("part_b.steps", "5000");
+        assert_eq!(5000, config.get::<u64>("part_b.steps", 0).unwrap());
+        assert_eq!(
+            vec![
+                ("name".to_string(), "a # b".to_string()),
+                ("steps".to_string(), "5000".to_string())
+            ],
+            config.section::<String>("part_b").unwrap()
+        );
+        assert!(config.get::<u64>("part_b.name", 0).is_err());
+        assert!(config.section::<u64>("part_b").is_err());
+        let bad_set = ["--set", "steps"].map(String::from).into_iter();
+        assert!(Config::from_args("./missing.toml", bad_set).is_err());
+    }
+
+    #[test]
+    fn check_malformed_line() {
+        let error = Config::parse("[bag]\nred 12\n").unwrap_err();
+        assert_eq!(2, error.line);
+    }
+}
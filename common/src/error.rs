@@ -0,0 +1,50 @@
+// The one error type every day reports malformed or unsolvable input with.
+// Malformed input must never panic, it has to come back as an InputError
+// pointing at the offending line when there is one.
+use std::{error::Error, fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputError {
+    /// 1-based line number, if the problem can be pinned to one line.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl InputError {
+    pub fn new(message: impl Into<String>) -> InputError {
+        InputError {
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    /// Error for the line at 0-based `index` of the input.
+    pub fn at(index: usize, message: impl Into<String>) -> InputError {
+        InputError {
+            line: Some(index + 1),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl Error for InputError {}
+
+/// Parses `text` found on the line at 0-based `index`.
+pub fn parse_number<T: FromStr>(text: &str, index: usize) -> Result<T, InputError> {
+    text.trim()
+        .parse::<T>()
+        .map_err(|_| InputError::at(index, format!("invalid number `{}`", text.trim())))
+}
+
+pub fn overflow() -> InputError {
+    InputError::new("answer does not fit in the result type")
+}
@@ -0,0 +1,279 @@
+// Exact arithmetic for the days that would otherwise reach for f64.
+// Everything is kept in i128, which is wide enough to hold the product of any
+// two i64 values, so puzzle coordinates around 1e14-1e15 can be multiplied
+// together without losing a single digit.
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+/// Widened integer used for all intermediate products.
+pub type Wide = i128;
+
+pub fn gcd(mut a: Wide, mut b: Wide) -> Wide {
+    while b != 0 {
+        let tmp = a;
+        a = b;
+        b = tmp % b;
+    }
+    a.abs()
+}
+
+pub fn lcm(a: Wide, b: Wide) -> Wide {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    (a / gcd(a, b) * b).abs()
+}
+
+/// `(g, x, y)` with `a * x + b * y == g == gcd(a, b)`.
+pub fn extended_gcd(a: Wide, b: Wide) -> (Wide, Wide, Wide) {
+    let (mut r0, mut r1) = (a, b);
+    let (mut x0, mut x1) = (1, 0);
+    let (mut y0, mut y1) = (0, 1);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+    if r0 < 0 {
+        return (-r0, -x0, -y0);
+    }
+    (r0, x0, y0)
+}
+
+/// Chinese remainder theorem for moduli that need not be coprime: the `x`
+/// with `x = a1 (mod m1)` and `x = a2 (mod m2)`, returned as
+/// `(x, lcm(m1, m2))` with `0 <= x < lcm`, or `None` when the two
+/// congruences contradict each other. Moduli must be positive and below
+/// about 1e18 so the intermediate products stay in range.
+pub fn crt((a1, m1): (Wide, Wide), (a2, m2): (Wide, Wide)) -> Option<(Wide, Wide)> {
+    let (g, p, _) = extended_gcd(m1, m2);
+    let diff = a2 - a1;
+    if diff % g != 0 {
+        return None;
+    }
+    let step = m2 / g;
+    let k = (diff / g).rem_euclid(step) * p.rem_euclid(step) % step;
+    let lcm = m1 * step;
+    Some(((a1 + m1 * k).rem_euclid(lcm), lcm))
+}
+
+/// Largest integer `r` with `r * r <= n`.
+///
+/// Starts from the f64 estimate and corrects it in both directions, so the
+/// result is exact even where the float square root is off by a few units.
+pub fn isqrt(n: Wide) -> Wide {
+    assert!(n >= 0, "square root of negative number {}", n);
+    let mut root = (n as f64).sqrt() as Wide;
+    while root > 0 && root.checked_mul(root).is_none_or(|sq| sq > n) {
+        root -= 1;
+    }
+    while (root + 1).checked_mul(root + 1).is_some_and(|sq| sq <= n) {
+        root += 1;
+    }
+    root
+}
+
+/// A fraction kept in lowest terms with a positive denominator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: Wide,
+    den: Wide,
+}
+
+impl Rational {
+    pub fn new(num: Wide, den: Wide) -> Rational {
+        assert!(den != 0, "rational with zero denominator");
+        let divisor = gcd(num, den).max(1) * den.signum();
+        Rational {
+            num: num / divisor,
+            den: den / divisor,
+        }
+    }
+
+    pub fn numer(&self) -> Wide {
+        self.num
+    }
+
+    pub fn denom(&self) -> Wide {
+        self.den
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    pub fn to_integer(&self) -> Option<Wide> {
+        if self.is_integer() {
+            Some(self.num)
+        } else {
+            None
+        }
+    }
+
+    pub fn floor(&self) -> Wide {
+        self.num.div_euclid(self.den)
+    }
+
+    pub fn ceil(&self) -> Wide {
+        -(-self.num).div_euclid(self.den)
+    }
+}
+
+impl From<Wide> for Rational {
+    fn from(value: Wide) -> Rational {
+        Rational { num: value, den: 1 }
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Rational {
+        Rational::from(value as Wide)
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+    fn add(self, other: Rational) -> Rational {
+        let den = lcm(self.den, other.den);
+        Rational::new(
+            self.num * (den / self.den) + other.num * (den / other.den),
+            den,
+        )
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+    fn sub(self, other: Rational) -> Rational {
+        self + -other
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+    fn mul(self, other: Rational) -> Rational {
+        // cross reduce first to keep the intermediate products small
+        let a = gcd(self.num, other.den).max(1);
+        let b = gcd(other.num, self.den).max(1);
+        Rational::new(
+            (self.num / a) * (other.num / b),
+            (self.den / b) * (other.den / a),
+        )
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+    fn div(self, other: Rational) -> Rational {
+        assert!(other.num != 0, "division of rational by zero");
+        self * Rational::new(other.den, other.num)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+    fn neg(self) -> Rational {
+        Rational {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+// Compares a.0 / a.1 with b.0 / b.1 (positive denominators) by their continued
+// fractions: integer parts first, and on a tie the inverted remainders, which
+// compare the other way round. Nothing is multiplied, so no operand is too
+// large.
+fn compare_fractions(mut a: (Wide, Wide), mut b: (Wide, Wide)) -> Ordering {
+    let mut flipped = false;
+    loop {
+        let order = a.0.div_euclid(a.1).cmp(&b.0.div_euclid(b.1));
+        let (rest_a, rest_b) = (a.0.rem_euclid(a.1), b.0.rem_euclid(b.1));
+        let order = match (order, rest_a == 0, rest_b == 0) {
+            (Ordering::Equal, true, true) => return Ordering::Equal,
+            (Ordering::Equal, true, false) => Ordering::Less,
+            (Ordering::Equal, false, true) => Ordering::Greater,
+            (Ordering::Equal, false, false) => {
+                (a, b) = ((a.1, rest_a), (b.1, rest_b));
+                flipped = !flipped;
+                continue;
+            }
+            (order, _, _) => order,
+        };
+        return if flipped { order.reverse() } else { order };
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Rational) -> Ordering {
+        compare_fractions((self.num, self.den), (other.num, other.den))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Rational) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn check_isqrt_boundaries() {
+        let big: Wide = 3_037_000_499;
+        assert_eq!(big, isqrt(big * big));
+        assert_eq!(big - 1, isqrt(big * big - 1));
+        assert_eq!(0, isqrt(0));
+        assert_eq!(4, isqrt(24));
+        assert_eq!(5, isqrt(25));
+    }
+
+    #[test]
+    fn check_crt() {
+        assert_eq!((2, 1, -1), extended_gcd(6, 4));
+        assert_eq!(Some((8, 15)), crt((2, 3), (3, 5)));
+        assert_eq!(Some((9, 12)), crt((1, 4), (3, 6)));
+        assert_eq!(Some((9, 12)), crt((-3, 4), (15, 6)));
+        assert_eq!(None, crt((2, 4), (3, 6)));
+    }
+
+    #[test]
+    fn check_rational_arithmetic() {
+        let half = Rational::new(2, -4);
+        assert_eq!(Rational::new(-1, 2), half);
+        assert_eq!(
+            Rational::from(1i64),
+            Rational::new(1, 3) * Rational::from(3i64)
+        );
+        assert_eq!(
+            Rational::new(1, 6),
+            Rational::new(1, 2) - Rational::new(1, 3)
+        );
+        assert!(Rational::new(1, 3) < Rational::new(1, 2));
+        assert_eq!((-1, 0), (half.floor(), half.ceil()));
+        // cross products of these do not fit in a Wide
+        let big = Wide::MAX / 3;
+        assert!(Rational::new(big, big - 1) < Rational::new(big - 1, big - 2));
+        assert!(Rational::new(big - 1, big) < Rational::new(big, big + 1));
+        assert!(Rational::new(-big, big - 1) < Rational::new(-big + 1, big - 1));
+        assert_eq!(
+            Ordering::Equal,
+            Rational::new(big, 7).cmp(&Rational::new(big, 7))
+        );
+    }
+}
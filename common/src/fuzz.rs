@@ -0,0 +1,209 @@
+// A dependency free fuzzer in the spirit of cargo-fuzz. Every day has a
+// `fuzz_parse_and_solve` test that hands its input character set, parser and
+// solvers to `fuzz_lines`, which mutates raw seed bytes, loads them like an
+// input file and fails on the first input that panics or does not finish in
+// time. Panics are bugs: malformed input has to surface as an
+// `InputError` instead.
+//
+// A short run happens with every `cargo test`. For a longer campaign (keep it
+// a debug build so integer overflow is caught too):
+//
+//     FUZZ_ITERATIONS=100000 cargo test fuzz
+//
+// `FUZZ_SEED` picks another random stream and `FUZZ_TIMEOUT_MS` changes how long
+// a single input may run. Failing inputs are written to `target/fuzz/`.
+use crate::input::decode;
+use std::{
+    env,
+    fs::{create_dir_all, read_dir, read_to_string, write},
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
+};
+
+const DEFAULT_ITERATIONS: u64 = 300;
+const DEFAULT_TIMEOUT_MS: u64 = 5000;
+
+// Bytes worth splicing into any puzzle input: separators, digits, a multi-byte
+// character, a stray CR and a few invalid UTF-8 bytes.
+const DICTIONARY: &[u8] = b"\n\r \t0123456789-+,:;|#.=@~{}()[]<>ABLRSZxmas?%&\xc3\xa9\xff\x00";
+const TOKENS: &[&str] = &["0", "-1", "99999999999999999999", "\r\n", "\n\n", "é", ""];
+
+pub fn fuzz<F>(seeds: &[&str], target: F)
+where
+    F: Fn(&[u8]) + Send + Sync + 'static,
+{
+    let iterations = env_or("FUZZ_ITERATIONS", DEFAULT_ITERATIONS);
+    let timeout = Duration::from_millis(env_or("FUZZ_TIMEOUT_MS", DEFAULT_TIMEOUT_MS));
+    let mut rng = Rng(env_or("FUZZ_SEED", 0x2023_1225).max(1));
+
+    let mut corpus: Vec<Vec<u8>> = seeds.iter().map(|s| s.as_bytes().to_vec()).collect();
+    corpus.extend(example_files());
+    corpus.push(Vec::new());
+    let max_len = corpus.iter().map(|c| c.len()).max().unwrap_or(0) * 2 + 16;
+
+    let target = Arc::new(target);
+    for iteration in 0..iterations {
+        let mut data = corpus[rng.below(corpus.len())].clone();
+        for _ in 0..1 + rng.below(4) {
+            mutate(&mut data, &mut rng);
+        }
+        data.truncate(max_len);
+
+        let (sender, receiver) = mpsc::channel();
+        let case = data.clone();
+        let t = Arc::clone(&target);
+        thread::spawn(move || {
+            t(&case);
+            let _ = sender.send(());
+        });
+        let failure = match receiver.recv_timeout(timeout) {
+            Ok(()) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => "panicked",
+            Err(mpsc::RecvTimeoutError::Timeout) => "timed out",
+        };
+        let artifact = format!("target/fuzz/crash-{}", iteration);
+        let _ = create_dir_all("target/fuzz").and_then(|_| write(&artifact, &data));
+        panic!(
+            "fuzz iteration {} {} on input {:?} (saved to {})",
+            iteration,
+            failure,
+            String::from_utf8_lossy(&data),
+            artifact
+        );
+    }
+}
+
+/// `fuzz` for solvers that take the input as lines. The raw bytes go through
+/// `decode` with the day's character set, the same path as the input file, so
+/// the solvers get exactly what `read_input` would hand them and invalid UTF-8
+/// or stray characters exercise the loader instead.
+pub fn fuzz_lines<A, F>(seeds: &[&str], allowed: A, target: F)
+where
+    A: Fn(char) -> bool + Send + Sync + 'static,
+    F: Fn(&Vec<String>) + Send + Sync + 'static,
+{
+    fuzz(seeds, move |data| {
+        if let Ok(input) = decode(data, &allowed) {
+            target(&input);
+        }
+    });
+}
+
+fn mutate(data: &mut Vec<u8>, rng: &mut Rng) {
+    let position = rng.below(data.len() + 1);
+    match rng.below(7) {
+        0 if position < data.len() => data[position] = pick_byte(data, rng),
+        1 => data.insert(position, pick_byte(data, rng)),
+        2 if position < data.len() => {
+            let end = (position + 1 + rng.below(8)).min(data.len());
+            data.drain(position..end);
+        }
+        3 if !data.is_empty() => {
+            // copy a chunk (often a whole line) somewhere else
+            let start = rng.below(data.len());
+            let end = (start + 1 + rng.below(32)).min(data.len());
+            let chunk = data[start..end].to_vec();
+            let at = rng.below(data.len() + 1);
+            data.splice(at..at, chunk);
+        }
+        4 => data.truncate(position),
+        5 => {
+            let token = TOKENS[rng.below(TOKENS.len())].as_bytes();
+            data.splice(position..position, token.iter().copied());
+        }
+        _ if position < data.len() && data[position].is_ascii_digit() => {
+            // replace a whole number to push values to their limits
+            let end = position
+                + data[position..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_digit())
+                    .count();
+            let token = TOKENS[rng.below(3)].as_bytes();
+            data.splice(position..end, token.iter().copied());
+        }
+        _ => (),
+    }
+}
+
+// Half the time reuse a byte already present so mutations stay close to the
+// grammar of the seed.
+fn pick_byte(data: &[u8], rng: &mut Rng) -> u8 {
+    if !data.is_empty() && rng.below(2) == 0 {
+        data[rng.below(data.len())]
+    } else {
+        DICTIONARY[rng.below(DICTIONARY.len())]
+    }
+}
+
+// The puzzle examples live next to the real input and are not committed, use
+// them as extra seeds when they are around.
+fn example_files() -> Vec<Vec<u8>> {
+    let mut files: Vec<Vec<u8>> = Vec::new();
+    if let Ok(entries) = read_dir("./inputs") {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with("example") {
+                if let Ok(text) = read_to_string(entry.path()) {
+                    files.push(text.into_bytes());
+                }
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+fn env_or(name: &str, default: u64) -> u64 {
+    env::var(name)
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(default)
+}
+
+// xorshift64, plenty for picking mutations
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            0
+        } else {
+            (self.next() % n as u64) as usize
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn check_fuzz_lines_only_passes_decoded_input() {
+        fuzz_lines(
+            &["ab\ncd\n"],
+            |c| c.is_ascii_lowercase(),
+            |input| {
+                assert!(input
+                    .iter()
+                    .all(|line| line.chars().all(|c| c.is_ascii_lowercase())));
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "panicked on input")]
+    fn check_fuzz_reports_panics() {
+        fuzz(&["1,2,3"], |data| {
+            if data.contains(&b'\n') {
+                panic!("newline");
+            }
+        });
+    }
+}
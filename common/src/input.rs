@@ -0,0 +1,100 @@
+// Loading puzzle inputs. Files saved on Windows or pasted from a browser come
+// with a byte order mark, CRLF line endings, trailing spaces or non-breaking
+// spaces; `normalize` takes care of the harmless ones and `validate` reports
+// everything else against the characters a day actually expects, so the
+// parsers only ever see clean ASCII lines.
+use crate::error::InputError;
+use std::fs::read;
+
+const BOM: char = '\u{feff}';
+
+/// Reads `filename` and hands its bytes to `decode`.
+pub fn read_input(
+    filename: &str,
+    allowed: impl Fn(char) -> bool,
+) -> Result<Vec<String>, InputError> {
+    let bytes = read(filename).map_err(|e| InputError::new(format!("{}: {}", filename, e)))?;
+    decode(&bytes, allowed)
+}
+
+/// Checks `bytes` are UTF-8, normalizes them and checks every character with
+/// `allowed`. Everything between the raw input and the lines the parsers see,
+/// so the fuzzer can take the same path as an input file.
+pub fn decode(bytes: &[u8], allowed: impl Fn(char) -> bool) -> Result<Vec<String>, InputError> {
+    let text = std::str::from_utf8(bytes)
+        .map_err(|e| InputError::new(format!("input is not valid UTF-8: {}", e)))?;
+    let lines = normalize(text);
+    validate(&lines, allowed)?;
+    Ok(lines)
+}
+
+/// Drops a leading BOM, splits on LF or CRLF, strips trailing whitespace from
+/// every line and blank lines from the end. Blank lines in between stay, days
+/// use them as separators.
+pub fn normalize(text: &str) -> Vec<String> {
+    let text = text.strip_prefix(BOM).unwrap_or(text);
+    let mut lines: Vec<String> = text
+        .lines()
+        .map(|line| line.trim_end().to_string())
+        .collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// The first character `allowed` rejects, with its line and column.
+pub fn validate(lines: &[String], allowed: impl Fn(char) -> bool) -> Result<(), InputError> {
+    for (i, line) in lines.iter().enumerate() {
+        if let Some((column, c)) = line.chars().enumerate().find(|(_, c)| !allowed(*c)) {
+            return Err(InputError::at(
+                i,
+                format!(
+                    "column {}: unexpected character {:?} (U+{:04X})",
+                    column + 1,
+                    c,
+                    c as u32
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn check_normalize() {
+        let text = "\u{feff}Time:  7 15\r\nDistance:  9 40  \r\n\r\n\tx\r\n\r\n\n";
+        assert_eq!(
+            vec!["Time:  7 15", "Distance:  9 40", "", "\tx"],
+            normalize(text)
+        );
+        assert_eq!(normalize("a\nb"), normalize("a\r\nb\r\n"));
+        assert!(normalize("").is_empty());
+    }
+
+    #[test]
+    fn check_validate() {
+        let lines = normalize("#.#\r\n.\u{a0}#\n");
+        let error = validate(&lines, |c| ".#".contains(c)).unwrap_err();
+        assert_eq!(Some(2), error.line);
+        assert_eq!(
+            "column 2: unexpected character '\\u{a0}' (U+00A0)",
+            error.message
+        );
+        assert_eq!(Ok(()), validate(&lines[..1], |c| ".#".contains(c)));
+    }
+
+    #[test]
+    fn check_decode() {
+        let allowed = |c: char| c.is_ascii_lowercase();
+        assert_eq!(
+            Ok(vec!["a".to_string(), "b".to_string()]),
+            decode(b"a\r\nb\n", allowed)
+        );
+        assert!(decode(b"a\xff", allowed).is_err());
+        assert_eq!(Some(2), decode(b"a\nB", allowed).unwrap_err().line);
+    }
+}
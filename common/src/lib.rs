@@ -0,0 +1,8 @@
+// Helpers shared between the days. Every day stays a standalone binary crate
+// and pulls this in as a path dependency when it needs it.
+pub mod aho_corasick;
+pub mod config;
+pub mod error;
+pub mod exact;
+pub mod fuzz;
+pub mod input;